@@ -0,0 +1,109 @@
+// Run with `cargo bench --bench decode`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use ume::ume8::{Decode, DecodeUnchecked, Ume8String};
+
+fn bench<F: FnMut() -> u64>(name: &str, bytes: usize, f: F) {
+    println!("{:<40} {:>10.1} MB/s", name, throughput(bytes, f));
+}
+
+// Reports `new` next to `baseline`, with the speedup of `new`.
+fn compare<F: FnMut() -> u64, G: FnMut() -> u64>(name: &str, bytes: usize, new: F, baseline: G) {
+    let new = throughput(bytes, new);
+    let baseline = throughput(bytes, baseline);
+    println!(
+        "{:<40} {:>10.1} MB/s   baseline {:>10.1} MB/s   {:>5.2}x",
+        name,
+        new,
+        baseline,
+        new / baseline
+    );
+}
+
+fn throughput<F: FnMut() -> u64>(bytes: usize, mut f: F) -> f64 {
+    // warm up
+    black_box(f());
+
+    let mut iterations = 0;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        black_box(f());
+        iterations += 1;
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (bytes * iterations) as f64 / elapsed / 1_000_000.0
+}
+
+const MASK_SEQ: u8 = 0b10000000;
+const MASK_SEQ_END: u8 = 0b00100000;
+const MASK_SEQ_START_DATA: u8 = 0b00111111;
+const MASK_SEQ_CONT_DATA: u8 = 0b00011111;
+
+// The bit-test loop `DecodeUnchecked` used before the byte table, as the baseline.
+fn decode_bit_test(bytes: &[u8]) -> u64 {
+    let mut iter = bytes.iter().cloned();
+    let mut sum = 0;
+
+    while let Some(first_byte) = iter.next() {
+        if first_byte & MASK_SEQ == 0 {
+            sum += first_byte as u64;
+            continue;
+        }
+
+        let mut data = (first_byte & MASK_SEQ_START_DATA) as u32;
+
+        loop {
+            let next_byte = iter.next().unwrap();
+
+            data = (data << 5) | ((next_byte & MASK_SEQ_CONT_DATA) as u32);
+
+            if next_byte & MASK_SEQ_END != 0 {
+                break;
+            }
+        }
+
+        sum += data as u64;
+    }
+
+    sum
+}
+
+fn main() {
+    let samples = [
+        (
+            "ascii",
+            "The quick brown fox jumps over the lazy dog. ".repeat(20_000),
+        ),
+        ("mixed", "aöӕธ💻 Grüße, Привет, こんにちは! ".repeat(10_000)),
+        ("cjk", "漢字仮名交じり文".repeat(40_000)),
+    ];
+
+    for (sample_name, sample) in &samples {
+        let string = Ume8String::from(sample.as_str());
+        let bytes = string.as_bytes();
+
+        compare(
+            &format!("{}/DecodeUnchecked", sample_name),
+            bytes.len(),
+            || {
+                DecodeUnchecked::new(bytes.iter().cloned())
+                    .map(|data| data as u64)
+                    .sum()
+            },
+            || decode_bit_test(bytes),
+        );
+        bench(&format!("{}/Decode", sample_name), bytes.len(), || {
+            Decode::new(bytes.iter().cloned())
+                .map(|data| data.unwrap() as u64)
+                .sum()
+        });
+        bench(&format!("{}/validate", sample_name), bytes.len(), || {
+            ume::ume8::validate(bytes).is_ok() as u64
+        });
+        bench(&format!("{}/to String", sample_name), bytes.len(), || {
+            String::from(string.as_str()).len() as u64
+        });
+    }
+}
@@ -0,0 +1,124 @@
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::{simd, table};
+
+// Whether every sequence of `bytes` encodes a Unicode scalar value in the shortest
+// possible form.
+pub(crate) fn is_canonical(bytes: &[u8]) -> bool {
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match table::decode_sequence(&bytes[index..]) {
+            Some((data, len)) if char::from_u32(data).is_some_and(|ch| ch.len_ume8() == len) => {
+                index += len
+            }
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// Re-encodes overlong sequences in their shortest form.
+///
+/// Other invalid bytes are kept as they are, so the result is valid UME-8 only if the input
+/// is valid apart from overlong sequences.
+pub fn canonicalize(bytes: &[u8]) -> Vec<u8> {
+    let mut canonical = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] < 0x80 {
+            let ascii_len = simd::ascii_len(&bytes[index..]);
+            canonical.extend_from_slice(&bytes[index..index + ascii_len]);
+            index += ascii_len;
+            continue;
+        }
+
+        match table::decode_sequence(&bytes[index..]) {
+            Some((data, len)) => {
+                match char::from_u32(data) {
+                    Some(ch) => canonical.extend_from_slice(ch.encode_ume8(&mut [0; 4]).as_bytes()),
+                    None => canonical.extend_from_slice(&bytes[index..index + len]),
+                }
+                index += len;
+            }
+            None => {
+                canonical.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::{validate, Ume8Str, Ume8String};
+
+    #[test]
+    fn test_canonicalize_boundaries() {
+        let cases: [(&[u8], char); 8] = [
+            (&[0b11000011, 0b10111111], '\u{7F}'),
+            (&[0b11000000, 0b10000100, 0b10100000], '\u{80}'),
+            (&[0b11000001, 0b10011111, 0b10111111], '\u{7FF}'),
+            (&[0b11000000, 0b10000010, 0b10000000, 0b10100000], '\u{800}'),
+            (
+                &[0b11000001, 0b10011111, 0b10011111, 0b10111111],
+                '\u{FFFF}',
+            ),
+            (&[0b11000000, 0b10100000], '\u{0}'),
+            (&[0b11000100, 0b10100000], '\u{80}'),
+            (
+                &[0b11000010, 0b10000000, 0b10000000, 0b10100000],
+                '\u{10000}',
+            ),
+        ];
+
+        for (bytes, ch) in cases {
+            let expected = Ume8String::from(ch.to_string());
+            let canonical = canonicalize(bytes);
+
+            assert_eq!(canonical, expected.as_bytes());
+            assert_eq!(validate(&canonical), Ok(()));
+            assert_eq!(validate(bytes).is_ok(), bytes == expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_keeps_invalid_bytes() {
+        let bytes = [
+            b'a', 0b10100001, 0b11000000, 0b10100001, 0b11000001, b'b', 0b11110110, 0b10000000,
+            0b10100000,
+        ];
+
+        assert_eq!(
+            canonicalize(&bytes),
+            vec![b'a', 0b10100001, 0x01, 0b11000001, b'b', 0b11110110, 0b10000000, 0b10100000]
+        );
+    }
+
+    #[test]
+    fn test_is_canonical_rejects_non_scalar_values() {
+        // overlong U+0000
+        assert!(!is_canonical(&[b'a', 0b11000000, 0b10100000]));
+        // shortest form of the surrogate U+D800
+        assert!(!is_canonical(&[0b11110110, 0b10000000, 0b10100000]));
+        // shortest form of U+110000
+        assert!(!is_canonical(&[
+            0b11100010, 0b10000000, 0b10000000, 0b10100000
+        ]));
+        assert!(is_canonical(&[b'a', 0b11000100, 0b10100000]));
+    }
+
+    #[test]
+    fn test_canonicalize_valid() {
+        for bytes in super::super::simd::tests::samples() {
+            if validate(&bytes).is_ok() {
+                assert_eq!(canonicalize(&bytes), bytes);
+                assert!(Ume8Str::from_bytes(&bytes).unwrap().is_canonical());
+            }
+        }
+    }
+}
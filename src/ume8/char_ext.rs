@@ -0,0 +1,111 @@
+use crate::ume8::str::Ume8Str;
+use crate::ume8::table;
+use crate::ume8::transcode::write_ume8_sequence;
+use crate::ume8::util::len_ume8;
+
+/// UME-8 counterparts of the UTF-8 methods of `char`.
+pub trait CharExt: Sized {
+    /// Number of bytes needed to encode the char in UME-8.
+    fn len_ume8(self) -> usize;
+
+    /// Encodes the char into the start of `dst` and returns the encoded part.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than [`CharExt::len_ume8`].
+    fn encode_ume8(self, dst: &mut [u8]) -> &mut Ume8Str;
+
+    /// Decodes the sequence at the start of `bytes` and returns the char along with the
+    /// length of the sequence, or `None` if it is not a valid canonical sequence.
+    fn decode_ume8(bytes: &[u8]) -> Option<(Self, usize)>;
+}
+
+impl CharExt for char {
+    #[inline]
+    fn len_ume8(self) -> usize {
+        len_ume8(self as u32).unwrap()
+    }
+
+    #[inline]
+    fn encode_ume8(self, dst: &mut [u8]) -> &mut Ume8Str {
+        let len = self.len_ume8();
+
+        assert!(
+            dst.len() >= len,
+            "encode_ume8: need {} bytes to encode U+{:X}, but the buffer has {}",
+            len,
+            self as u32,
+            dst.len()
+        );
+
+        write_ume8_sequence(self as u32, &mut dst[..len]);
+
+        unsafe { Ume8Str::from_inner_mut(&mut dst[..len]) }
+    }
+
+    #[inline]
+    fn decode_ume8(bytes: &[u8]) -> Option<(Self, usize)> {
+        let (data, len) = table::decode_sequence(bytes)?;
+        let ch = char::from_u32(data)?;
+
+        if ch.len_ume8() != len {
+            return None;
+        }
+
+        Some((ch, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::Ume8String;
+
+    #[test]
+    fn test_encode_decode() {
+        for ch in [
+            'a',
+            '\u{0}',
+            '\u{7F}',
+            'ö',
+            '\u{7FF}',
+            'ธ',
+            '\u{FFFF}',
+            '💻',
+            char::MAX,
+        ] {
+            let mut buffer = [0; 4];
+            let encoded = ch.encode_ume8(&mut buffer);
+
+            assert_eq!(encoded.len(), ch.len_ume8());
+            assert_eq!(
+                encoded.as_bytes(),
+                Ume8String::from(ch.to_string()).as_bytes()
+            );
+            assert_eq!(char::decode_ume8(&buffer), Some((ch, ch.len_ume8())));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encode_buffer_too_small() {
+        '💻'.encode_ume8(&mut [0; 3]);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(char::decode_ume8(&[]), None);
+        assert_eq!(char::decode_ume8(&[0b10100001]), None);
+        assert_eq!(char::decode_ume8(&[0b11000001, b'a']), None);
+        assert_eq!(char::decode_ume8(&[0b11000001, 0b10000001]), None);
+        // surrogate
+        assert_eq!(
+            char::decode_ume8(&[0b11110110, 0b10000000, 0b10100000]),
+            None
+        );
+        // overlong
+        assert_eq!(char::decode_ume8(&[0b11000000, 0b10100001]), None);
+        // trailing bytes are not part of the sequence
+        assert_eq!(char::decode_ume8(&[b'a', 0b10100001]), Some(('a', 1)));
+    }
+}
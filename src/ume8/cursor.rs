@@ -0,0 +1,167 @@
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::str::Ume8Str;
+
+/// A char cursor for hand-written lexers, that can move in both directions.
+///
+/// Positions are byte offsets into the text, so slicing between two positions is O(1).
+#[derive(Clone, Debug)]
+pub struct Cursor<'a> {
+    text: &'a Ume8Str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(text: &'a Ume8Str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    pub fn text(&self) -> &'a Ume8Str {
+        self.text
+    }
+
+    pub fn byte_pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    /// Remaining text after the cursor.
+    pub fn rest(&self) -> &'a Ume8Str {
+        &self.text[self.pos..]
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    pub fn peek_back(&self) -> Option<char> {
+        self.text[..self.pos].chars().next_back()
+    }
+
+    pub fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_ume8();
+        Some(ch)
+    }
+
+    // The previous char ends right before the cursor, with either a singleton or an end
+    // byte, so it can be decoded from the back.
+    pub fn bump_back(&mut self) -> Option<char> {
+        let ch = self.peek_back()?;
+        self.pos -= ch.len_ume8();
+        Some(ch)
+    }
+
+    /// Moves past all chars matching `predicate` and returns them.
+    pub fn eat_while<P: FnMut(char) -> bool>(&mut self, mut predicate: P) -> &'a Ume8Str {
+        let start = self.pos;
+
+        while let Some(ch) = self.peek() {
+            if !predicate(ch) {
+                break;
+            }
+
+            self.pos += ch.len_ume8();
+        }
+
+        self.slice_from(start)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds or not on a char boundary.
+    pub fn reset_to(&mut self, pos: usize) {
+        assert!(
+            self.text.is_char_boundary(pos),
+            "byte position {} is out of bounds or not on a char boundary",
+            pos
+        );
+
+        self.pos = pos;
+    }
+
+    /// Text between `mark`, a position returned by [`Cursor::byte_pos`], and the cursor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mark` is after the cursor or not on a char boundary.
+    pub fn slice_from(&self, mark: usize) -> &'a Ume8Str {
+        &self.text[mark..self.pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::Ume8String;
+
+    #[test]
+    fn test_cursor_forward() {
+        let text = Ume8String::from("aö💻 ӕ");
+        let mut cursor = Cursor::new(&text);
+
+        assert_eq!(cursor.peek(), Some('a'));
+        assert_eq!(cursor.peek_nth(2), Some('💻'));
+        assert_eq!(cursor.bump(), Some('a'));
+        assert_eq!(cursor.byte_pos(), 1);
+        assert_eq!(cursor.bump(), Some('ö'));
+        assert_eq!(cursor.bump(), Some('💻'));
+        assert_eq!(cursor.bump(), Some(' '));
+        assert_eq!(cursor.bump(), Some('ӕ'));
+        assert!(cursor.is_eof());
+        assert_eq!(cursor.bump(), None);
+        assert_eq!(cursor.peek(), None);
+    }
+
+    #[test]
+    fn test_cursor_backward() {
+        let text = Ume8String::from("aö💻");
+        let mut cursor = Cursor::new(&text);
+        cursor.reset_to(text.len());
+
+        assert_eq!(cursor.peek_back(), Some('💻'));
+        assert_eq!(cursor.bump_back(), Some('💻'));
+        assert_eq!(cursor.byte_pos(), 3);
+        assert_eq!(cursor.bump_back(), Some('ö'));
+        assert_eq!(cursor.bump_back(), Some('a'));
+        assert_eq!(cursor.bump_back(), None);
+        assert_eq!(cursor.byte_pos(), 0);
+    }
+
+    #[test]
+    fn test_cursor_eat_while_and_slice() {
+        let text = Ume8String::from("ident_ö1 = 42");
+        let mut cursor = Cursor::new(&text);
+
+        let mark = cursor.byte_pos();
+        let ident = cursor.eat_while(|ch| ch.is_alphanumeric() || ch == '_');
+
+        assert_eq!(ident, Ume8String::from("ident_ö1").as_str());
+        assert_eq!(cursor.slice_from(mark), ident);
+
+        cursor.eat_while(char::is_whitespace);
+        assert_eq!(cursor.bump(), Some('='));
+        cursor.eat_while(char::is_whitespace);
+
+        let number = cursor.eat_while(|ch| ch.is_ascii_digit());
+        assert_eq!(number.parse::<u32>(), Ok(42));
+
+        cursor.reset_to(mark);
+        assert_eq!(cursor.peek(), Some('i'));
+        assert_eq!(cursor.rest(), text.as_str());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cursor_reset_inside_char() {
+        let text = Ume8String::from("aö");
+        let mut cursor = Cursor::new(&text);
+        cursor.reset_to(2);
+    }
+}
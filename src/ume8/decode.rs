@@ -1,8 +1,11 @@
+use crate::ume8::table::{self, ByteKind, State};
 use crate::ume8::util;
-use crate::ume8::{
-    MASK_SEQ, MASK_SEQ_CONT_DATA, MASK_SEQ_END, MASK_SEQ_START, MASK_SEQ_START_DATA,
-};
+use crate::ume8::util::len_ume8;
+use crate::ume8::MASK_SEQ;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::iter::{DoubleEndedIterator, FusedIterator, Iterator};
+use std::num::NonZeroUsize;
 
 #[derive(Clone)]
 #[must_use = "iterators are lazy and do nothing unless consumed"]
@@ -21,6 +24,42 @@ where
     pub fn new(iter: I) -> Self {
         Self { iter }
     }
+
+    /// Skips `n` sequences by scanning for their last bytes, without decoding them.
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let mut remaining = n;
+
+        while remaining > 0 {
+            match self.iter.next() {
+                Some(byte) if util::is_sequence_end(&byte) => remaining -= 1,
+                Some(_) => {}
+                None => return Err(NonZeroUsize::new(remaining).unwrap()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<I> DecodeUnchecked<I>
+where
+    I: DoubleEndedIterator<Item = u8>,
+{
+    /// Skips `n` sequences from the back by scanning for their first bytes, without
+    /// decoding them.
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let mut remaining = n;
+
+        while remaining > 0 {
+            match self.iter.next_back() {
+                Some(byte) if util::is_char_start(&byte) => remaining -= 1,
+                Some(_) => {}
+                None => return Err(NonZeroUsize::new(remaining).unwrap()),
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<I> Iterator for DecodeUnchecked<I>
@@ -30,35 +69,55 @@ where
     type Item = u32;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let first_byte = self.iter.next()?;
+        let mut byte = self.iter.next()?;
 
-        if first_byte & MASK_SEQ == 0 {
-            return Some(first_byte as u32);
+        if byte & MASK_SEQ == 0 {
+            return Some(byte as u32);
         }
 
-        let mut data = (first_byte & MASK_SEQ_START_DATA) as u32;
+        let mut data = 0;
 
         loop {
-            let next_byte = self.iter.next().unwrap();
+            let kind = table::kind_of(byte);
+            data = table::accumulate(data, byte, kind);
 
-            data = (data << 5) | ((next_byte & MASK_SEQ_CONT_DATA) as u32);
-
-            if next_byte & MASK_SEQ_END != 0 {
-                break;
+            if matches!(kind, ByteKind::Singleton | ByteKind::End) {
+                return Some(data);
             }
-        }
 
-        Some(data)
+            byte = self.iter.next().unwrap();
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         let (lower, upper) = self.iter.size_hint();
-        ((lower + 3) / 4, upper)
+        (lower.div_ceil(4), upper)
     }
 
     fn count(self) -> usize {
         util::count_sequences_unchecked(self.iter)
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_by(n).ok()?;
+        self.next()
+    }
+
+    // Only keeps the bytes of the current sequence, so that just the last one is decoded.
+    fn last(self) -> Option<Self::Item> {
+        let mut bytes = [0; util::MAX_SEQUENCE_LEN];
+        let mut len = 0;
+
+        for byte in self.iter {
+            if util::is_char_start(&byte) {
+                len = 0;
+            }
+            bytes[len] = byte;
+            len += 1;
+        }
+
+        DecodeUnchecked::new(bytes[..len].iter().copied()).next()
+    }
 }
 
 impl<I> DoubleEndedIterator for DecodeUnchecked<I>
@@ -66,33 +125,229 @@ where
     I: DoubleEndedIterator<Item = u8>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
-        let last_byte = self.iter.next_back()?;
+        let mut byte = self.iter.next_back()?;
+        let mut data = 0;
+        let mut bit_count = 0;
+
+        loop {
+            let kind = table::kind_of(byte);
+            data |= ((byte & table::DATA_MASKS[kind as usize]) as u32) << bit_count;
+
+            if matches!(kind, ByteKind::Singleton | ByteKind::Start) {
+                return Some(data);
+            }
 
-        if last_byte & MASK_SEQ == 0 {
-            return Some(last_byte as u32);
+            byte = self.iter.next_back().unwrap();
+            bit_count += 5;
         }
+    }
 
-        let mut data = (last_byte & MASK_SEQ_CONT_DATA) as u32;
-        let mut bit_count: u8 = 5;
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_back_by(n).ok()?;
+        self.next_back()
+    }
+}
 
-        loop {
-            let prev_byte = self.iter.next_back().unwrap();
+impl<I> FusedIterator for DecodeUnchecked<I> where I: Iterator<Item = u8> + FusedIterator<Item = u8> {}
 
-            if prev_byte & MASK_SEQ_START != 0 {
-                data |= ((prev_byte & MASK_SEQ_START_DATA) as u32) << bit_count;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A continuation or end byte outside of a sequence.
+    UnexpectedByte(u8),
+    /// A sequence interrupted by a singleton, a start byte or the end of the input.
+    IncompleteSequence,
+    /// A sequence with more than 4 bytes.
+    SequenceTooLong,
+    /// A sequence that is longer than needed for its value.
+    OverlongSequence,
+    /// A sequence whose value is not a Unicode scalar value, only returned when decoding
+    /// chars.
+    InvalidChar(u32),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnexpectedByte(byte) => write!(f, "unexpected byte {:#04x}", byte),
+            DecodeError::IncompleteSequence => write!(f, "incomplete sequence"),
+            DecodeError::SequenceTooLong => write!(f, "sequence longer than 4 bytes"),
+            DecodeError::OverlongSequence => write!(f, "overlong sequence"),
+            DecodeError::InvalidChar(data) => write!(f, "invalid char {:#x}", data),
+        }
+    }
+}
+
+impl Error for DecodeError {}
 
-                break;
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Decode<I>
+where
+    I: Iterator<Item = u8>,
+{
+    iter: I,
+    // first byte of the next sequence, read while detecting an incomplete sequence
+    pending: Option<u8>,
+}
+
+impl<I> Decode<I>
+where
+    I: Iterator<Item = u8>,
+{
+    #[inline]
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            pending: None,
+        }
+    }
+}
+
+impl<I> Iterator for Decode<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = Result<u32, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut byte = self.pending.take().or_else(|| self.iter.next())?;
+
+        if byte & MASK_SEQ == 0 {
+            return Some(Ok(byte as u32));
+        }
+
+        let mut state = State::Accept;
+        let mut data = 0;
+        let mut len = 0;
+
+        loop {
+            let kind = table::kind_of(byte);
+
+            state = match table::transition(state, kind) {
+                State::Reject => {
+                    return Some(Err(match (state, kind) {
+                        (State::Accept, _) => DecodeError::UnexpectedByte(byte),
+                        (_, ByteKind::Singleton | ByteKind::Start) => {
+                            self.pending = Some(byte);
+                            DecodeError::IncompleteSequence
+                        }
+                        _ => DecodeError::SequenceTooLong,
+                    }));
+                }
+                next_state => next_state,
+            };
+            data = table::accumulate(data, byte, kind);
+            len += 1;
+
+            if state == State::Accept {
+                if len_ume8(data) != Some(len) {
+                    return Some(Err(DecodeError::OverlongSequence));
+                }
+
+                return Some(Ok(data));
             }
 
-            data |= ((prev_byte & MASK_SEQ_CONT_DATA) as u32) << bit_count;
-            bit_count += 5;
+            byte = match self.iter.next() {
+                Some(byte) => byte,
+                None => return Some(Err(DecodeError::IncompleteSequence)),
+            };
         }
+    }
 
-        Some(data)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let pending = self.pending.is_some() as usize;
+        (
+            (lower + pending).div_ceil(4),
+            upper.and_then(|upper| upper.checked_add(pending)),
+        )
     }
 }
 
-impl<I> FusedIterator for DecodeUnchecked<I> where I: Iterator<Item = u8> + FusedIterator<Item = u8> {}
+impl<I> FusedIterator for Decode<I> where I: FusedIterator<Item = u8> {}
+
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct DecodeChars<I>
+where
+    I: Iterator<Item = u8>,
+{
+    iter: Decode<I>,
+}
+
+impl<I> DecodeChars<I>
+where
+    I: Iterator<Item = u8>,
+{
+    #[inline]
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter: Decode::new(iter),
+        }
+    }
+}
+
+impl<I> Iterator for DecodeChars<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = Result<char, DecodeError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.iter
+                .next()?
+                .and_then(|data| char::from_u32(data).ok_or(DecodeError::InvalidChar(data))),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I> FusedIterator for DecodeChars<I> where I: FusedIterator<Item = u8> {}
+
+/// Decodes chars, replacing every invalid sequence with `U+FFFD REPLACEMENT CHARACTER`.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct DecodeLossy<I>
+where
+    I: Iterator<Item = u8>,
+{
+    iter: DecodeChars<I>,
+}
+
+impl<I> DecodeLossy<I>
+where
+    I: Iterator<Item = u8>,
+{
+    #[inline]
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter: DecodeChars::new(iter),
+        }
+    }
+}
+
+impl<I> Iterator for DecodeLossy<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I> FusedIterator for DecodeLossy<I> where I: FusedIterator<Item = u8> {}
 
 #[derive(Clone)]
 pub struct ToCharUnchecked<Iter>
@@ -139,6 +394,10 @@ where
     {
         self.iter.last().map(|data| char::from_u32(data).unwrap())
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(|data| char::from_u32(data).unwrap())
+    }
 }
 
 impl<Iter> DoubleEndedIterator for ToCharUnchecked<Iter>
@@ -150,6 +409,30 @@ where
             .next_back()
             .map(|data| char::from_u32(data).unwrap())
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter
+            .nth_back(n)
+            .map(|data| char::from_u32(data).unwrap())
+    }
+}
+
+impl<I> ToCharUnchecked<DecodeUnchecked<I>>
+where
+    I: Iterator<Item = u8>,
+{
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        self.iter.advance_by(n)
+    }
+}
+
+impl<I> ToCharUnchecked<DecodeUnchecked<I>>
+where
+    I: DoubleEndedIterator<Item = u8>,
+{
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        self.iter.advance_back_by(n)
+    }
 }
 
 impl<Iter> ExactSizeIterator for ToCharUnchecked<Iter>
@@ -221,6 +504,123 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_checked() {
+        for (decoded, encoded) in super::super::tests::data() {
+            let decoder = Decode::new(encoded.clone().into_iter());
+            let decoder_data = decoder.collect::<Result<Vec<u32>, DecodeError>>();
+
+            assert_eq!(decoder_data, Ok(decoded));
+        }
+    }
+
+    #[test]
+    fn test_decode_checked_invalid() {
+        let encoded = vec![
+            0b10100001, // lone end byte
+            b'a', 0b11000001, // interrupted by a singleton
+            b'b', 0b11000001, // interrupted by a start byte
+            0b11000001, 0b10000001, 0b10000001, 0b10000001, // too long
+            0b11000000, 0b10100001, // overlong
+            0b10100001, 0b11000001, // interrupted by the end of the input
+        ];
+        let decoder_data = Decode::new(encoded.into_iter()).collect::<Vec<_>>();
+
+        assert_eq!(
+            decoder_data,
+            vec![
+                Err(DecodeError::UnexpectedByte(0b10100001)),
+                Ok('a' as u32),
+                Err(DecodeError::IncompleteSequence),
+                Ok('b' as u32),
+                Err(DecodeError::IncompleteSequence),
+                Err(DecodeError::SequenceTooLong),
+                Err(DecodeError::OverlongSequence),
+                Err(DecodeError::UnexpectedByte(0b10100001)),
+                Err(DecodeError::IncompleteSequence),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_checked_matches_validate() {
+        for bytes in super::super::simd::tests::samples() {
+            let decoded = Decode::new(bytes.iter().cloned()).collect::<Result<Vec<u32>, _>>();
+            let valid = decoded
+                .is_ok_and(|decoded| decoded.iter().all(|&data| char::from_u32(data).is_some()));
+
+            assert_eq!(valid, crate::ume8::validate(&bytes).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_decode_nth() {
+        for (decoded, encoded) in super::super::tests::data() {
+            for n in 0..=decoded.len() {
+                let mut decoder = DecodeUnchecked::new(encoded.clone().into_iter());
+
+                assert_eq!(decoder.nth(n), decoded.get(n).copied());
+                assert_eq!(decoder.next(), decoded.get(n + 1).copied());
+
+                let mut decoder = DecodeUnchecked::new(encoded.clone().into_iter());
+                let index = decoded.len().checked_sub(n + 1);
+
+                assert_eq!(decoder.nth_back(n), index.map(|index| decoded[index]));
+                assert_eq!(
+                    decoder.next_back(),
+                    index
+                        .and_then(|index| index.checked_sub(1))
+                        .map(|index| decoded[index])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_last() {
+        for (decoded, encoded) in super::super::tests::data() {
+            let decoder = DecodeUnchecked::new(encoded.clone().into_iter());
+
+            assert_eq!(decoder.last(), decoded.last().copied());
+        }
+    }
+
+    #[test]
+    fn test_decode_advance_by() {
+        for (decoded, encoded) in super::super::tests::data() {
+            let mut decoder = DecodeUnchecked::new(encoded.clone().into_iter());
+
+            assert_eq!(decoder.advance_by(decoded.len()), Ok(()));
+            assert_eq!(decoder.next(), None);
+
+            let mut decoder = DecodeUnchecked::new(encoded.clone().into_iter());
+
+            assert_eq!(
+                decoder.advance_by(decoded.len() + 2),
+                Err(NonZeroUsize::new(2).unwrap())
+            );
+
+            let mut decoder = DecodeUnchecked::new(encoded.clone().into_iter());
+
+            assert_eq!(
+                decoder.advance_back_by(decoded.len() + 1),
+                Err(NonZeroUsize::new(1).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_to_char_nth() {
+        let encoded = vec![97, 239, 170, 33, 204, 140, 189, 0, 195, 157, 144, 160, 100];
+        let mut decoder = ToCharUnchecked::new(DecodeUnchecked::new(encoded.into_iter()));
+
+        assert_eq!(decoder.nth(1), Some('\u{5EA}'));
+        assert_eq!(decoder.nth_back(1), Some('\u{1F600}'));
+        assert_eq!(decoder.advance_by(1), Ok(()));
+        assert_eq!(decoder.advance_back_by(1), Ok(()));
+        assert_eq!(decoder.collect::<String>(), "\u{319D}");
+    }
+
     #[test]
     fn test_decode_size_hint() {
         for (decoded, encoded) in super::super::tests::data() {
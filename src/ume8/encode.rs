@@ -1,6 +1,11 @@
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::util::len_ume8;
 use crate::ume8::{
     MASK_SEQ, MASK_SEQ_CONT_DATA, MASK_SEQ_END, MASK_SEQ_START, MASK_SEQ_START_DATA,
 };
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::iter::FusedIterator;
 
 #[derive(Clone)]
 #[must_use = "iterators are lazy and do nothing unless consumed"]
@@ -9,8 +14,14 @@ where
     I: Iterator<Item = u32>,
 {
     pub iter: I,
+    // bytes `next_index..end_index` of the sequence at the front
     buffer: [u8; 4],
     next_index: u8,
+    end_index: u8,
+    // bytes `back_start..back_end` of the sequence at the back
+    back_buffer: [u8; 4],
+    back_start: u8,
+    back_end: u8,
 }
 
 impl<I> EncodeUnchecked<I>
@@ -23,55 +34,64 @@ where
             iter,
             buffer: [0, 0, 0, 0],
             next_index: 4,
+            end_index: 4,
+            back_buffer: [0, 0, 0, 0],
+            back_start: 4,
+            back_end: 4,
         }
     }
 
     fn set_data(&mut self, data: u32) {
+        (self.buffer, self.next_index) = Self::encode_sequence(data);
+        self.end_index = 4;
+    }
+
+    fn set_back_data(&mut self, data: u32) {
+        (self.back_buffer, self.back_start) = Self::encode_sequence(data);
+        self.back_end = 4;
+    }
+
+    // Returns the sequence right-aligned in the buffer, along with the index of its start.
+    fn encode_sequence(data: u32) -> ([u8; 4], u8) {
         // 1 byte
         if data & 0b1111_1111_1111_1111_1111_1111_1000_0000 == 0 {
-            self.buffer = [0, 0, 0, data as u8];
-            self.next_index = 3;
-
-            return;
+            return ([0, 0, 0, data as u8], 3);
         }
 
         // 2 bytes
         if data & 0b1111_1111_1111_1111_1111_1000_0000_0000 == 0 {
-            self.buffer = [
+            let buffer = [
                 0,
                 0,
                 (((data >> 5) as u8) & MASK_SEQ_START_DATA) | MASK_SEQ | MASK_SEQ_START,
                 ((data as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ | MASK_SEQ_END,
             ];
-            self.next_index = 2;
 
-            return;
+            return (buffer, 2);
         }
 
         // 3 bytes
         if data & 0b1111_1111_1111_1111_0000_0000_0000_0000 == 0 {
-            self.buffer = [
+            let buffer = [
                 0,
                 (((data >> (5 + 5)) as u8) & MASK_SEQ_START_DATA) | MASK_SEQ | MASK_SEQ_START,
                 (((data >> 5) as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ,
                 ((data as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ | MASK_SEQ_END,
             ];
-            self.next_index = 1;
 
-            return;
+            return (buffer, 1);
         }
 
         // 4 bytes
         if data & 0b1111_1111_1110_0000_0000_0000_0000_0000 == 0 {
-            self.buffer = [
+            let buffer = [
                 (((data >> (5 + 5 + 5)) as u8) & MASK_SEQ_START_DATA) | MASK_SEQ | MASK_SEQ_START,
                 (((data >> (5 + 5)) as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ,
                 (((data >> 5) as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ,
                 ((data as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ | MASK_SEQ_END,
             ];
-            self.next_index = 0;
 
-            return;
+            return (buffer, 0);
         }
 
         panic!("trying to encode more than 21 bits of data");
@@ -86,8 +106,16 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         // end of current sequence
-        if self.next_index >= 4 {
-            let next_data = self.iter.next()?;
+        if self.next_index >= self.end_index {
+            let next_data = match self.iter.next() {
+                Some(next_data) => next_data,
+                // the rest of the sequence started by `next_back`
+                None if self.back_start < self.back_end => {
+                    self.back_start += 1;
+                    return Some(self.back_buffer[self.back_start as usize - 1]);
+                }
+                None => return None,
+            };
 
             // 1 byte
             if next_data & 0b1111_1111_1111_1111_1111_1111_1000_0000 == 0 {
@@ -102,6 +130,149 @@ where
 
         Some(next_byte)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.end_index.saturating_sub(self.next_index) as usize
+            + (self.back_end - self.back_start) as usize;
+        let (lower, upper) = self.iter.size_hint();
+
+        (
+            lower.saturating_add(buffered),
+            upper
+                .and_then(|upper| upper.checked_mul(4))
+                .and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I> DoubleEndedIterator for EncodeUnchecked<I>
+where
+    I: DoubleEndedIterator<Item = u32>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // start of current sequence
+        if self.back_start >= self.back_end {
+            match self.iter.next_back() {
+                Some(data) => self.set_back_data(data),
+                // the rest of the sequence started by `next`
+                None if self.next_index < self.end_index => {
+                    self.end_index -= 1;
+                    return Some(self.buffer[self.end_index as usize]);
+                }
+                None => return None,
+            }
+        }
+
+        self.back_end -= 1;
+
+        Some(self.back_buffer[self.back_end as usize])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncodeError {
+    data: u32,
+}
+
+impl EncodeError {
+    /// Value that could not be encoded.
+    pub fn data(&self) -> u32 {
+        self.data
+    }
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#x} is not a Unicode scalar value", self.data)
+    }
+}
+
+impl Error for EncodeError {}
+
+/// Encodes Unicode scalar values, yielding an error for every other value.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct TryEncode<I>
+where
+    I: Iterator<Item = u32>,
+{
+    iter: I,
+    buffer: [u8; 4],
+    next_index: u8,
+    len: u8,
+}
+
+impl<I> TryEncode<I>
+where
+    I: Iterator<Item = u32>,
+{
+    #[inline]
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            buffer: [0, 0, 0, 0],
+            next_index: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<I> Iterator for TryEncode<I>
+where
+    I: Iterator<Item = u32>,
+{
+    type Item = Result<u8, EncodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index < self.len {
+            self.next_index += 1;
+            return Some(Ok(self.buffer[self.next_index as usize - 1]));
+        }
+
+        let data = self.iter.next()?;
+        let ch = match char::from_u32(data) {
+            Some(ch) => ch,
+            None => return Some(Err(EncodeError { data })),
+        };
+
+        self.len = ch.encode_ume8(&mut self.buffer).len() as u8;
+        self.next_index = 1;
+
+        Some(Ok(self.buffer[0]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = (self.len - self.next_index) as usize;
+        let (lower, upper) = self.iter.size_hint();
+
+        (
+            lower.saturating_add(buffered),
+            upper
+                .and_then(|upper| upper.checked_mul(4))
+                .and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I> FusedIterator for TryEncode<I> where I: FusedIterator<Item = u32> {}
+
+#[inline]
+pub(crate) fn sequence_len(data: u32) -> usize {
+    len_ume8(data).expect("trying to encode more than 21 bits of data")
+}
+
+pub fn encoded_len<I>(iter: I) -> usize
+where
+    I: IntoIterator,
+    I::Item: Into<u32>,
+{
+    iter.into_iter().map(|data| sequence_len(data.into())).sum()
+}
+
+/// Number of bytes needed to encode `s`, which is always `s.len()`.
+#[inline]
+pub fn encoded_len_of_str(s: &str) -> usize {
+    s.len()
 }
 
 #[cfg(test)]
@@ -118,6 +289,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encode_sequences_reverse() {
+        for (decoded, encoded) in super::super::tests::data() {
+            let encoder = EncodeUnchecked::new(decoded.clone().into_iter());
+            let mut encoded_reverse = encoded.clone();
+            encoded_reverse.reverse();
+
+            assert_eq!(encoder.rev().collect::<Vec<u8>>(), encoded_reverse);
+        }
+    }
+
+    #[test]
+    fn test_encode_from_both_ends() {
+        for (decoded, encoded) in super::super::tests::data() {
+            // every split point, including ones inside of a sequence
+            for front_len in 0..=encoded.len() {
+                let mut encoder = EncodeUnchecked::new(decoded.clone().into_iter());
+                let mut front = encoder.by_ref().take(front_len).collect::<Vec<u8>>();
+                let back = encoder.rev().collect::<Vec<u8>>();
+
+                front.extend(back.into_iter().rev());
+                assert_eq!(front, encoded);
+            }
+
+            // alternating ends
+            let mut encoder = EncodeUnchecked::new(decoded.clone().into_iter());
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            while let Some(byte) = encoder.next() {
+                front.push(byte);
+
+                match encoder.next_back() {
+                    Some(byte) => back.push(byte),
+                    None => break,
+                }
+            }
+
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, encoded);
+            assert_eq!(encoder.next(), None);
+            assert_eq!(encoder.next_back(), None);
+        }
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        for (decoded, encoded) in super::super::tests::data() {
+            assert_eq!(encoded_len(decoded.iter().cloned()), encoded.len());
+
+            let string = decoded
+                .iter()
+                .map(|&data| char::from_u32(data).unwrap())
+                .collect::<String>();
+
+            assert_eq!(encoded_len(string.chars()), encoded.len());
+            assert_eq!(encoded_len_of_str(&string), encoded.len());
+        }
+    }
+
+    #[test]
+    fn test_encode_size_hint() {
+        for (decoded, encoded) in super::super::tests::data() {
+            let mut encoder = EncodeUnchecked::new(decoded.clone().into_iter());
+
+            for remaining in (0..=encoded.len()).rev() {
+                let (lower, upper) = encoder.size_hint();
+
+                assert!(lower <= remaining);
+                assert!(upper.unwrap() >= remaining);
+
+                encoder.next();
+            }
+        }
+    }
+
     #[test]
     #[should_panic]
     #[allow(unused_must_use)]
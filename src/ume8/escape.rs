@@ -0,0 +1,371 @@
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::str::{Chars, Ume8Str};
+use crate::ume8::string::Ume8String;
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Write};
+use std::iter::{Chain, FlatMap, Flatten, FusedIterator};
+
+// longest escape is `\u{10ffff}`
+const ESCAPE_MAX_LEN: usize = 10;
+
+#[derive(Clone)]
+struct EscapeBuffer {
+    chars: [char; ESCAPE_MAX_LEN],
+    start: u8,
+    end: u8,
+}
+
+impl FromIterator<char> for EscapeBuffer {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut buffer = Self {
+            chars: ['\0'; ESCAPE_MAX_LEN],
+            start: 0,
+            end: 0,
+        };
+
+        for ch in iter {
+            buffer.chars[buffer.end as usize] = ch;
+            buffer.end += 1;
+        }
+
+        buffer
+    }
+}
+
+impl Iterator for EscapeBuffer {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+
+        let ch = self.chars[self.start as usize];
+        self.start += 1;
+
+        Some(ch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.start) as usize;
+        (len, Some(len))
+    }
+}
+
+// Same as `char::escape_debug`, except that grapheme extenders are not escaped.
+// This matches the behaviour of `str::escape_debug` for every char but the first.
+fn escape_debug_continue(ch: char) -> EscapeBuffer {
+    if ch.is_ascii() {
+        return ch.escape_debug().collect();
+    }
+
+    let mut buffer = [0; 5];
+    buffer[0] = b'a';
+    let len = ch.encode_utf8(&mut buffer[1..]).len();
+    let s = std::str::from_utf8(&buffer[..=len]).unwrap();
+
+    s.escape_debug().skip(1).collect()
+}
+
+type EscapeDebugFirst = Flatten<std::option::IntoIter<std::char::EscapeDebug>>;
+type EscapeDebugContinue<'a> = FlatMap<Chars<'a>, EscapeBuffer, fn(char) -> EscapeBuffer>;
+
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct EscapeDebug<'a> {
+    inner: Chain<EscapeDebugFirst, EscapeDebugContinue<'a>>,
+}
+
+impl<'a> EscapeDebug<'a> {
+    pub(crate) fn new(mut chars: Chars<'a>) -> Self {
+        let first = chars.next().map(|ch| ch.escape_debug());
+
+        Self {
+            inner: first
+                .into_iter()
+                .flatten()
+                .chain(chars.flat_map(escape_debug_continue as fn(char) -> EscapeBuffer)),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct EscapeDefault<'a> {
+    inner: FlatMap<Chars<'a>, std::char::EscapeDefault, fn(char) -> std::char::EscapeDefault>,
+}
+
+impl<'a> EscapeDefault<'a> {
+    pub(crate) fn new(chars: Chars<'a>) -> Self {
+        Self {
+            inner: chars.flat_map(char::escape_default as fn(char) -> std::char::EscapeDefault),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct EscapeUnicode<'a> {
+    inner: FlatMap<Chars<'a>, std::char::EscapeUnicode, fn(char) -> std::char::EscapeUnicode>,
+}
+
+impl<'a> EscapeUnicode<'a> {
+    pub(crate) fn new(chars: Chars<'a>) -> Self {
+        Self {
+            inner: chars.flat_map(char::escape_unicode as fn(char) -> std::char::EscapeUnicode),
+        }
+    }
+}
+
+macro_rules! escape_impls {
+    ($($name:ident),+) => {$(
+        impl Iterator for $name<'_> {
+            type Item = char;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl FusedIterator for $name<'_> {}
+
+        impl Display for $name<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                self.clone().try_for_each(|ch| f.write_char(ch))
+            }
+        }
+    )+};
+}
+
+escape_impls!(EscapeDebug, EscapeDefault, EscapeUnicode);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnescapeErrorKind {
+    LoneBackslash,
+    UnknownEscape(char),
+    InvalidHexEscape,
+    InvalidUnicodeEscape,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnescapeError {
+    kind: UnescapeErrorKind,
+    offset: usize,
+}
+
+impl UnescapeError {
+    pub fn kind(&self) -> UnescapeErrorKind {
+        self.kind
+    }
+
+    /// Byte offset of the backslash that starts the invalid escape.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Display for UnescapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            UnescapeErrorKind::LoneBackslash => write!(f, "lone backslash")?,
+            UnescapeErrorKind::UnknownEscape(ch) => write!(f, "unknown escape `\\{}`", ch)?,
+            UnescapeErrorKind::InvalidHexEscape => write!(f, "invalid `\\x` escape")?,
+            UnescapeErrorKind::InvalidUnicodeEscape => write!(f, "invalid `\\u{{...}}` escape")?,
+        }
+
+        write!(f, " at byte offset {}", self.offset)
+    }
+}
+
+impl Error for UnescapeError {}
+
+fn parse_hex_escape<I: Iterator<Item = char>>(chars: &mut I) -> Option<char> {
+    let high = chars.next()?.to_digit(16)?;
+    let low = chars.next()?.to_digit(16)?;
+    let data = (high << 4) | low;
+
+    if data > 0x7F {
+        return None;
+    }
+
+    char::from_u32(data)
+}
+
+fn parse_unicode_escape<I: Iterator<Item = char>>(chars: &mut I) -> Option<char> {
+    if chars.next()? != '{' {
+        return None;
+    }
+
+    let mut data: u32 = 0;
+    let mut digit_count = 0;
+
+    loop {
+        let ch = chars.next()?;
+
+        if ch == '}' {
+            break;
+        }
+
+        if ch == '_' && digit_count > 0 {
+            continue;
+        }
+
+        data = (data << 4) | ch.to_digit(16)?;
+        digit_count += 1;
+
+        if digit_count > 6 {
+            return None;
+        }
+    }
+
+    if digit_count == 0 {
+        return None;
+    }
+
+    char::from_u32(data)
+}
+
+/// Reverses `escape_debug`, `escape_default` and `escape_unicode` by parsing the escape
+/// sequences of Rust string literals.
+pub fn unescape(s: &Ume8Str) -> Result<Ume8String, UnescapeError> {
+    let mut string = Ume8String::with_capacity(s.len());
+    let consumed_len = Cell::new(0);
+    let mut chars = s
+        .chars()
+        .inspect(|ch| consumed_len.set(consumed_len.get() + ch.len_ume8()));
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            string.push(ch);
+
+            continue;
+        }
+
+        let offset = consumed_len.get() - 1;
+        let error = |kind| UnescapeError { kind, offset };
+
+        let unescaped = match chars.next() {
+            None => return Err(error(UnescapeErrorKind::LoneBackslash)),
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('n') => '\n',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('\'') => '\'',
+            Some('"') => '"',
+            Some('x') => parse_hex_escape(&mut chars)
+                .ok_or_else(|| error(UnescapeErrorKind::InvalidHexEscape))?,
+            Some('u') => parse_unicode_escape(&mut chars)
+                .ok_or_else(|| error(UnescapeErrorKind::InvalidUnicodeEscape))?,
+            Some(other) => return Err(error(UnescapeErrorKind::UnknownEscape(other))),
+        };
+
+        string.push(unescaped);
+    }
+
+    Ok(string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [&str; 7] = [
+        "",
+        "abc",
+        "tab\tnew\nline\r\0",
+        "quote ' \" \\",
+        "aöӕธ💻",
+        "\u{301}a\u{301}",
+        "\u{7f}\u{200b}\u{10ffff}",
+    ];
+
+    #[test]
+    fn test_escape_matches_str() {
+        for sample in SAMPLES {
+            let string = Ume8String::from(sample);
+
+            assert_eq!(
+                string.escape_debug().collect::<String>(),
+                sample.escape_debug().collect::<String>()
+            );
+            assert_eq!(
+                string.escape_default().collect::<String>(),
+                sample.escape_default().collect::<String>()
+            );
+            assert_eq!(
+                string.escape_unicode().collect::<String>(),
+                sample.escape_unicode().collect::<String>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape_display() {
+        for sample in SAMPLES {
+            let string = Ume8String::from(sample);
+
+            assert_eq!(
+                string.escape_debug().to_string(),
+                sample.escape_debug().to_string()
+            );
+            assert_eq!(
+                string.escape_default().to_string(),
+                sample.escape_default().to_string()
+            );
+            assert_eq!(
+                string.escape_unicode().to_string(),
+                sample.escape_unicode().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_unescape_round_trip() {
+        for sample in SAMPLES {
+            let string = Ume8String::from(sample);
+
+            for escaped in [
+                string.escape_debug().collect::<Ume8String>(),
+                string.escape_default().collect::<Ume8String>(),
+                string.escape_unicode().collect::<Ume8String>(),
+            ] {
+                assert_eq!(unescape(&escaped), Ok(string.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unescape_literal_escapes() {
+        let escaped = Ume8String::from(r"\x41\u{1_F4BB}ö\'");
+
+        assert_eq!(unescape(&escaped), Ok(Ume8String::from("A💻ö'")));
+    }
+
+    #[test]
+    fn test_unescape_errors() {
+        let cases = [
+            (r"abc\", UnescapeErrorKind::LoneBackslash, 3),
+            (r"ö\q", UnescapeErrorKind::UnknownEscape('q'), 2),
+            (r"\x8f", UnescapeErrorKind::InvalidHexEscape, 0),
+            (r"\x4", UnescapeErrorKind::InvalidHexEscape, 0),
+            (r"a\u{d800}", UnescapeErrorKind::InvalidUnicodeEscape, 1),
+            (r"a\n\u{}", UnescapeErrorKind::InvalidUnicodeEscape, 3),
+            (r"\u{1234567}", UnescapeErrorKind::InvalidUnicodeEscape, 0),
+        ];
+
+        for (escaped, kind, offset) in cases {
+            let error = unescape(&Ume8String::from(escaped)).unwrap_err();
+
+            assert_eq!(error.kind(), kind);
+            assert_eq!(error.offset(), offset);
+        }
+    }
+}
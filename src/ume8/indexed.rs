@@ -0,0 +1,377 @@
+use crate::ume8::simd;
+use crate::ume8::str::Ume8Str;
+use crate::ume8::string::Ume8String;
+use crate::ume8::util::is_char_start;
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, Deref, RangeBounds};
+
+const CHECKPOINT_INTERVAL: usize = 64;
+
+/// A `Ume8String` that keeps the byte offset of at least every 64th char, so that
+/// conversions between char and byte indices take O(log n) instead of O(n).
+///
+/// Edits shift the checkpoints after the edited range and only rescan the chars between
+/// the checkpoints around it.
+#[derive(Clone)]
+pub struct IndexedUme8String {
+    string: Ume8String,
+    // sorted, starting with the start of the string, and at most `CHECKPOINT_INTERVAL`
+    // chars apart
+    checkpoints: Vec<Checkpoint>,
+    char_len: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+struct Checkpoint {
+    byte_index: usize,
+    char_index: usize,
+}
+
+impl IndexedUme8String {
+    pub fn new(string: Ume8String) -> Self {
+        let len = string.len();
+        let mut indexed = Self {
+            string,
+            checkpoints: vec![Checkpoint::default()],
+            char_len: 0,
+        };
+        indexed.update(0, 0, len);
+        indexed
+    }
+
+    pub fn as_str(&self) -> &Ume8Str {
+        &self.string
+    }
+
+    pub fn into_inner(self) -> Ume8String {
+        self.string
+    }
+
+    pub fn char_len(&self) -> usize {
+        self.char_len
+    }
+
+    pub fn char_to_byte(&self, char_index: usize) -> Option<usize> {
+        if char_index > self.char_len {
+            return None;
+        }
+
+        let checkpoint = self.checkpoints[self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.char_index <= char_index)
+            - 1];
+
+        self.string[checkpoint.byte_index..]
+            .char_to_byte(char_index - checkpoint.char_index)
+            .map(|offset| checkpoint.byte_index + offset)
+    }
+
+    pub fn byte_to_char(&self, byte_index: usize) -> Option<usize> {
+        if !self.string.is_char_boundary(byte_index) {
+            return None;
+        }
+
+        let checkpoint = self.checkpoints[self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.byte_index <= byte_index)
+            - 1];
+
+        Some(
+            checkpoint.char_index
+                + simd::count_char_starts(
+                    &self.string.as_bytes()[checkpoint.byte_index..byte_index],
+                ),
+        )
+    }
+
+    pub fn char_at(&self, char_index: usize) -> Option<char> {
+        let start = self.char_to_byte(char_index)?;
+        self.string[start..].chars().next()
+    }
+
+    pub fn char_slice<R: RangeBounds<usize>>(&self, range: R) -> Option<&Ume8Str> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => self.char_to_byte(start)?,
+            Bound::Excluded(&start) => self.char_to_byte(start.checked_add(1)?)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => self.char_to_byte(end.checked_add(1)?)?,
+            Bound::Excluded(&end) => self.char_to_byte(end)?,
+            Bound::Unbounded => self.string.len(),
+        };
+
+        self.string.get(start..end)
+    }
+
+    pub fn push(&mut self, ch: char) {
+        let start = self.string.len();
+        self.string.push(ch);
+        self.update(start, start, self.string.len());
+    }
+
+    pub fn push_str(&mut self, string: &Ume8Str) {
+        let start = self.string.len();
+        self.string.push_str(string);
+        self.update(start, start, self.string.len());
+    }
+
+    pub fn insert_str(&mut self, byte_index: usize, string: &Ume8Str) {
+        self.string.insert_str(byte_index, string);
+        self.update(byte_index, byte_index, byte_index + string.len());
+    }
+
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &Ume8Str) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.string.len(),
+        };
+
+        self.string.replace_range(range, replace_with);
+        self.update(start, end, start + replace_with.len());
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        let old_len = self.string.len();
+        self.string.truncate(new_len);
+        self.update(self.string.len(), old_len, self.string.len());
+    }
+
+    // Updates the checkpoints after the bytes `start..old_end` were replaced with the
+    // bytes `start..new_end`.
+    //
+    // The chars between the last checkpoint before the edit and the first one after it
+    // are rescanned, and all later checkpoints are shifted.
+    fn update(&mut self, start: usize, old_end: usize, new_end: usize) {
+        // the first checkpoint is never affected, as it is the start of the string
+        let first = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.byte_index < start)
+            .max(1)
+            - 1;
+        let next = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.byte_index < old_end)
+            .max(first + 1);
+
+        let block_start = self.checkpoints[first];
+        let (block_end, old_char_end) = match self.checkpoints.get(next) {
+            Some(checkpoint) => (
+                checkpoint.byte_index - old_end + new_end,
+                checkpoint.char_index,
+            ),
+            None => (self.string.len(), self.char_len),
+        };
+
+        let mut block_checkpoints = Vec::new();
+        let mut char_index = block_start.char_index;
+        let mut chars_since_checkpoint = 0;
+
+        for (offset, byte) in self.string.as_bytes()[block_start.byte_index..block_end]
+            .iter()
+            .enumerate()
+        {
+            if !is_char_start(byte) {
+                continue;
+            }
+
+            if chars_since_checkpoint == CHECKPOINT_INTERVAL {
+                block_checkpoints.push(Checkpoint {
+                    byte_index: block_start.byte_index + offset,
+                    char_index,
+                });
+                chars_since_checkpoint = 0;
+            }
+
+            char_index += 1;
+            chars_since_checkpoint += 1;
+        }
+
+        // chars after the block only move, so the checkpoints after it stay valid once
+        // shifted by the change in bytes and chars
+        let new_char_end = char_index;
+        for checkpoint in &mut self.checkpoints[next..] {
+            checkpoint.byte_index = checkpoint.byte_index - old_end + new_end;
+            checkpoint.char_index = checkpoint.char_index - old_char_end + new_char_end;
+        }
+        self.char_len = self.char_len - old_char_end + new_char_end;
+
+        self.checkpoints.splice(first + 1..next, block_checkpoints);
+    }
+}
+
+impl Default for IndexedUme8String {
+    fn default() -> Self {
+        Self::new(Ume8String::new())
+    }
+}
+
+// Checkpoints depend on the edits made, so only the strings are compared.
+impl PartialEq for IndexedUme8String {
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+    }
+}
+
+impl Eq for IndexedUme8String {}
+
+impl Hash for IndexedUme8String {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.string.hash(state);
+    }
+}
+
+impl Deref for IndexedUme8String {
+    type Target = Ume8Str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.string
+    }
+}
+
+impl AsRef<Ume8Str> for IndexedUme8String {
+    fn as_ref(&self) -> &Ume8Str {
+        &self.string
+    }
+}
+
+impl From<Ume8String> for IndexedUme8String {
+    fn from(string: Ume8String) -> Self {
+        Self::new(string)
+    }
+}
+
+impl From<IndexedUme8String> for Ume8String {
+    fn from(indexed: IndexedUme8String) -> Self {
+        indexed.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize) -> String {
+        "aöӕธ💻".chars().cycle().take(len).collect()
+    }
+
+    fn assert_index_matches(indexed: &IndexedUme8String, string: &str) {
+        let char_offsets = string
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([string.len()])
+            .collect::<Vec<_>>();
+
+        assert_eq!(indexed.as_str(), Ume8String::from(string).as_str());
+        assert_eq!(indexed.char_len(), char_offsets.len() - 1);
+        assert_eq!(indexed.char_to_byte(char_offsets.len()), None);
+
+        for (char_index, &offset) in char_offsets.iter().enumerate() {
+            assert_eq!(indexed.char_to_byte(char_index), Some(offset));
+            assert_eq!(indexed.byte_to_char(offset), Some(char_index));
+            assert_eq!(indexed.char_at(char_index), string[offset..].chars().next());
+            assert_eq!(indexed.as_str().char_to_byte(char_index), Some(offset));
+            assert_eq!(indexed.as_str().byte_to_char(offset), Some(char_index));
+        }
+    }
+
+    #[test]
+    fn test_indexed_lookup() {
+        for len in [0, 1, 63, 64, 65, 200, 1000] {
+            let string = sample(len);
+            let indexed = IndexedUme8String::new(Ume8String::from(string.as_str()));
+
+            assert_index_matches(&indexed, &string);
+        }
+    }
+
+    #[test]
+    fn test_indexed_non_boundary() {
+        let indexed = IndexedUme8String::new(Ume8String::from(sample(200).as_str()));
+
+        assert_eq!(indexed.byte_to_char(2), None);
+        assert_eq!(indexed.byte_to_char(indexed.len() + 1), None);
+    }
+
+    #[test]
+    fn test_indexed_char_slice() {
+        let string = sample(300);
+        let indexed = IndexedUme8String::new(Ume8String::from(string.as_str()));
+        let expected = string.chars().skip(70).take(100).collect::<String>();
+
+        assert_eq!(
+            indexed.char_slice(70..170),
+            Some(Ume8String::from(expected.as_str()).as_str())
+        );
+        assert_eq!(
+            indexed.as_str().char_slice(70..=169),
+            Some(Ume8String::from(expected.as_str()).as_str())
+        );
+        assert_eq!(indexed.char_slice(..301), None);
+        assert_eq!(indexed.as_str().char_slice(290..301), None);
+        assert_eq!(indexed.char_slice(..), Some(indexed.as_str()));
+    }
+
+    #[test]
+    fn test_indexed_edits() {
+        let mut string = sample(500);
+        let mut indexed = IndexedUme8String::new(Ume8String::from(string.as_str()));
+
+        let offset = string.char_indices().nth(100).unwrap().0;
+        string.insert_str(offset, "xyz💻");
+        indexed.insert_str(offset, &Ume8String::from("xyz💻"));
+        assert_index_matches(&indexed, &string);
+
+        let start = string.char_indices().nth(10).unwrap().0;
+        let end = string.char_indices().nth(300).unwrap().0;
+        string.replace_range(start..end, "ӕ");
+        indexed.replace_range(start..end, &Ume8String::from("ӕ"));
+        assert_index_matches(&indexed, &string);
+
+        string.push('ธ');
+        indexed.push('ธ');
+        string.push_str(&sample(100));
+        indexed.push_str(&Ume8String::from(sample(100).as_str()));
+        assert_index_matches(&indexed, &string);
+
+        let offset = string.char_indices().nth(65).unwrap().0;
+        string.truncate(offset);
+        indexed.truncate(offset);
+        assert_index_matches(&indexed, &string);
+    }
+
+    #[test]
+    fn test_indexed_many_edits() {
+        let mut string = sample(300);
+        let mut indexed = IndexedUme8String::new(Ume8String::from(string.as_str()));
+
+        for step in 0..60 {
+            let char_len = string.chars().count();
+            let start = string.char_indices().nth(step * 37 % (char_len + 1));
+            let start = start.map_or(string.len(), |(offset, _)| offset);
+            let end = string[start..]
+                .char_indices()
+                .nth(step * 13 % 150)
+                .map_or(string.len(), |(offset, _)| start + offset);
+            let replacement = sample(step * 7 % 100);
+
+            string.replace_range(start..end, &replacement);
+            indexed.replace_range(start..end, &Ume8String::from(replacement.as_str()));
+            assert_index_matches(&indexed, &string);
+
+            let mut blocks = indexed.checkpoints.windows(2);
+            assert!(
+                blocks.all(|pair| pair[1].char_index - pair[0].char_index <= CHECKPOINT_INTERVAL)
+            );
+        }
+
+        assert!(indexed == IndexedUme8String::new(Ume8String::from(string.as_str())));
+        assert_eq!(IndexedUme8String::default().char_to_byte(0), Some(0));
+    }
+}
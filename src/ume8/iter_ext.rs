@@ -0,0 +1,174 @@
+use crate::ume8::decode::{Decode, DecodeChars, DecodeLossy, DecodeUnchecked, ToCharUnchecked};
+use crate::ume8::encode::{EncodeUnchecked, TryEncode};
+use std::iter::Map;
+
+/// Values of `I` converted to `u32`, as passed to the encoders of [`EncodeUme8`].
+pub type IntoScalars<I> = Map<I, fn(<I as Iterator>::Item) -> u32>;
+
+/// Adds UME-8 encoding to iterators over chars and scalar values.
+pub trait EncodeUme8: Iterator + Sized
+where
+    Self::Item: Into<u32>,
+{
+    /// Encodes every value without checking that it is a Unicode scalar value.
+    ///
+    /// # Panics
+    ///
+    /// The returned iterator panics on values with more than 21 bits.
+    fn encode_ume8(self) -> EncodeUnchecked<IntoScalars<Self>> {
+        EncodeUnchecked::new(self.map(Into::into as fn(Self::Item) -> u32))
+    }
+
+    /// Encodes every value, yielding an error for values that are not Unicode scalar
+    /// values.
+    fn try_encode_ume8(self) -> TryEncode<IntoScalars<Self>> {
+        TryEncode::new(self.map(Into::into as fn(Self::Item) -> u32))
+    }
+}
+
+impl<I> EncodeUme8 for I
+where
+    I: Iterator,
+    I::Item: Into<u32>,
+{
+}
+
+/// Adds UME-8 decoding to iterators over bytes.
+pub trait DecodeUme8: Iterator<Item = u8> + Sized {
+    /// Decodes scalar values, yielding an error for every invalid sequence.
+    fn decode_ume8(self) -> Decode<Self> {
+        Decode::new(self)
+    }
+
+    /// Decodes scalar values from bytes that are known to be valid UME-8.
+    fn decode_ume8_unchecked(self) -> DecodeUnchecked<Self> {
+        DecodeUnchecked::new(self)
+    }
+
+    /// Decodes chars, yielding an error for every invalid sequence.
+    fn decode_ume8_chars(self) -> DecodeChars<Self> {
+        DecodeChars::new(self)
+    }
+
+    /// Decodes chars from bytes that are known to be valid UME-8.
+    ///
+    /// # Panics
+    ///
+    /// The returned iterator panics on sequences that are not Unicode scalar values.
+    fn decode_ume8_chars_unchecked(self) -> ToCharUnchecked<DecodeUnchecked<Self>> {
+        ToCharUnchecked::new(DecodeUnchecked::new(self))
+    }
+
+    /// Decodes chars, replacing every invalid sequence with `U+FFFD REPLACEMENT CHARACTER`.
+    fn decode_ume8_lossy(self) -> DecodeLossy<Self> {
+        DecodeLossy::new(self)
+    }
+}
+
+impl<I> DecodeUme8 for I where I: Iterator<Item = u8> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::{DecodeError, Ume8String};
+
+    #[test]
+    fn test_encode_ume8() {
+        for (decoded, encoded) in super::super::tests::data() {
+            assert_eq!(
+                decoded.iter().copied().encode_ume8().collect::<Vec<u8>>(),
+                encoded
+            );
+            assert_eq!(
+                decoded
+                    .iter()
+                    .copied()
+                    .try_encode_ume8()
+                    .collect::<Result<Vec<u8>, _>>(),
+                Ok(encoded)
+            );
+        }
+
+        let string = Ume8String::from("aö💻");
+        assert_eq!(
+            "aö💻".chars().encode_ume8().collect::<Vec<u8>>(),
+            string.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_try_encode_ume8_invalid() {
+        let encoded = [0x61u32, 0xD800, 0x62, 0x110000]
+            .into_iter()
+            .try_encode_ume8()
+            .map(|result| result.map_err(|error| error.data()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            encoded,
+            vec![Ok(b'a'), Err(0xD800), Ok(b'b'), Err(0x110000)]
+        );
+    }
+
+    #[test]
+    fn test_decode_ume8() {
+        let string = Ume8String::from("aö💻");
+        let bytes = string.as_bytes().iter().copied();
+
+        let expected = vec!['a', 'ö', '💻'];
+        let expected_data = expected.iter().map(|&ch| ch as u32).collect::<Vec<u32>>();
+
+        assert_eq!(
+            bytes.clone().decode_ume8().collect::<Result<Vec<_>, _>>(),
+            Ok(expected_data.clone())
+        );
+        assert_eq!(
+            bytes.clone().decode_ume8_unchecked().collect::<Vec<_>>(),
+            expected_data
+        );
+        assert_eq!(
+            bytes
+                .clone()
+                .decode_ume8_chars()
+                .collect::<Result<Vec<_>, _>>(),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            bytes
+                .clone()
+                .decode_ume8_chars_unchecked()
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(bytes.decode_ume8_lossy().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_decode_ume8_invalid() {
+        let bytes = [
+            b'a', 0b10100001, 0b11110110, 0b10000000, 0b10100000, 0b11000001,
+        ];
+
+        assert_eq!(
+            bytes
+                .iter()
+                .copied()
+                .decode_ume8_chars()
+                .collect::<Vec<_>>(),
+            vec![
+                Ok('a'),
+                Err(DecodeError::UnexpectedByte(0b10100001)),
+                Err(DecodeError::InvalidChar(0xD800)),
+                Err(DecodeError::IncompleteSequence),
+            ]
+        );
+        assert_eq!(
+            bytes
+                .iter()
+                .copied()
+                .decode_ume8_lossy()
+                .collect::<String>(),
+            "a\u{FFFD}\u{FFFD}\u{FFFD}"
+        );
+    }
+}
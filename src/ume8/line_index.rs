@@ -0,0 +1,290 @@
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::str::Ume8Str;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LineCol {
+    /// Zero-based line number.
+    pub line: usize,
+    /// Zero-based column in chars.
+    pub col: usize,
+    /// Zero-based column in UTF-16 code units.
+    pub col_utf16: usize,
+}
+
+/// Maps byte offsets in a document to lines and columns and back.
+///
+/// Lines are terminated by `\n`, `\r\n` or `\r`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LineIndex {
+    // byte offset of the start of every line, the first line starts at 0
+    line_starts: Vec<usize>,
+    len: usize,
+}
+
+// Pushes the start of every line after `start` within `bytes[start..end]`.
+fn scan_line_starts(bytes: &[u8], range: Range<usize>, line_starts: &mut Vec<usize>) {
+    let mut index = range.start;
+
+    while index < range.end {
+        match bytes[index] {
+            b'\n' => line_starts.push(index + 1),
+            b'\r' if bytes.get(index + 1) == Some(&b'\n') => {
+                line_starts.push(index + 2);
+                index += 1;
+            }
+            b'\r' => line_starts.push(index + 1),
+            _ => {}
+        }
+
+        index += 1;
+    }
+}
+
+impl LineIndex {
+    pub fn new(text: &Ume8Str) -> Self {
+        let mut line_starts = vec![0];
+        scan_line_starts(text.as_bytes(), 0..text.len(), &mut line_starts);
+
+        Self {
+            line_starts,
+            len: text.len(),
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte range of `line`, including its line break.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(line)?;
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(self.len);
+        Some(start..end)
+    }
+
+    pub fn line_of(&self, offset: usize) -> Option<usize> {
+        if offset > self.len {
+            return None;
+        }
+
+        Some(self.line_starts.partition_point(|&start| start <= offset) - 1)
+    }
+
+    /// Returns `None` if `offset` is out of bounds or not on a char boundary.
+    pub fn line_col(&self, text: &Ume8Str, offset: usize) -> Option<LineCol> {
+        let line = self.line_of(offset)?;
+        let line_start = self.line_starts[line];
+        let line_text = text.get(line_start..offset)?;
+
+        let (col, col_utf16) = line_text.chars().fold((0, 0), |(col, col_utf16), ch| {
+            (col + 1, col_utf16 + ch.len_utf16())
+        });
+
+        Some(LineCol {
+            line,
+            col,
+            col_utf16,
+        })
+    }
+
+    /// Returns the byte offset of the char `col` in `line`. `col` may point to the end of
+    /// the line, but not past its line break.
+    pub fn offset(&self, text: &Ume8Str, line: usize, col: usize) -> Option<usize> {
+        let range = self.line_range(line)?;
+        let line_text = text.get(range.clone())?;
+
+        let mut offset = range.start;
+        let mut chars = line_text.chars();
+        for _ in 0..col {
+            let ch = chars.next()?;
+            if ch == '\n' || ch == '\r' {
+                return None;
+            }
+            offset += ch.len_ume8();
+        }
+
+        Some(offset)
+    }
+
+    /// Same as [`LineIndex::offset`], but with a column in UTF-16 code units. Columns in the
+    /// middle of a surrogate pair are rejected.
+    pub fn offset_utf16(&self, text: &Ume8Str, line: usize, col_utf16: usize) -> Option<usize> {
+        let range = self.line_range(line)?;
+        let line_text = text.get(range.clone())?;
+
+        let mut offset = range.start;
+        let mut col = 0;
+        let mut chars = line_text.chars();
+        while col < col_utf16 {
+            let ch = chars.next()?;
+            if ch == '\n' || ch == '\r' {
+                return None;
+            }
+            offset += ch.len_ume8();
+            col += ch.len_utf16();
+        }
+
+        if col != col_utf16 {
+            return None;
+        }
+
+        Some(offset)
+    }
+
+    /// Updates the index after the bytes in `range` have been replaced with `new_len`
+    /// bytes. `text` is the document after the edit.
+    pub fn update(&mut self, text: &Ume8Str, range: Range<usize>, new_len: usize) {
+        assert!(range.start <= range.end && range.end <= self.len);
+        assert_eq!(text.len(), self.len - range.len() + new_len);
+
+        // Rescan from the start of the line before the edit, since a `\r` at its end may
+        // now be followed by a `\n`, and up to the first line start that is preceded by at
+        // least one unchanged byte.
+        let first_line = self.line_of(range.start).unwrap().saturating_sub(1);
+        let first_kept = self
+            .line_starts
+            .partition_point(|&start| start <= range.end + 1);
+
+        let rescan_start = self.line_starts[first_line];
+        let rescan_end = match self.line_starts.get(first_kept) {
+            Some(&start) => start + new_len - range.len(),
+            None => text.len(),
+        };
+
+        let mut line_starts = Vec::with_capacity(first_kept - first_line);
+        scan_line_starts(text.as_bytes(), rescan_start..rescan_end, &mut line_starts);
+        if first_kept < self.line_starts.len() {
+            // the line start at `rescan_end` is scanned again, but kept from the old index
+            line_starts.retain(|&start| start < rescan_end);
+        }
+
+        let shifted = self.line_starts[first_kept..]
+            .iter()
+            .map(|&start| start + new_len - range.len());
+        let tail = shifted.collect::<Vec<_>>();
+
+        self.line_starts.truncate(first_line + 1);
+        self.line_starts.extend(line_starts);
+        self.line_starts.extend(tail);
+        self.len = text.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::Ume8String;
+
+    #[test]
+    fn test_line_starts() {
+        let cases = [
+            ("", vec![0]),
+            ("a", vec![0]),
+            ("a\nb", vec![0, 2]),
+            ("a\r\nb\rc\n", vec![0, 3, 5, 7]),
+            ("\r\r\n\n", vec![0, 1, 3, 4]),
+        ];
+
+        for (text, line_starts) in cases {
+            let index = LineIndex::new(&Ume8String::from(text));
+
+            assert_eq!(index.line_starts, line_starts);
+        }
+    }
+
+    #[test]
+    fn test_line_col() {
+        let text = Ume8String::from("aö\r\n💻x\rӕ");
+        let index = LineIndex::new(&text);
+
+        let line_col = |line, col, col_utf16| {
+            Some(LineCol {
+                line,
+                col,
+                col_utf16,
+            })
+        };
+
+        assert_eq!(index.line_col(&text, 0), line_col(0, 0, 0));
+        assert_eq!(index.line_col(&text, 3), line_col(0, 2, 2));
+        assert_eq!(index.line_col(&text, 4), line_col(0, 3, 3));
+        assert_eq!(index.line_col(&text, 5), line_col(1, 0, 0));
+        assert_eq!(index.line_col(&text, 9), line_col(1, 1, 2));
+        assert_eq!(index.line_col(&text, 10), line_col(1, 2, 3));
+        assert_eq!(index.line_col(&text, 11), line_col(2, 0, 0));
+        assert_eq!(index.line_col(&text, 13), line_col(2, 1, 1));
+        assert_eq!(index.line_col(&text, 2), None);
+        assert_eq!(index.line_col(&text, 14), None);
+    }
+
+    #[test]
+    fn test_offset() {
+        let text = Ume8String::from("aö\r\n💻x\rӕ");
+        let index = LineIndex::new(&text);
+
+        assert_eq!(index.offset(&text, 0, 2), Some(3));
+        assert_eq!(index.offset(&text, 0, 3), None);
+        assert_eq!(index.offset(&text, 1, 1), Some(9));
+        assert_eq!(index.offset(&text, 2, 1), Some(13));
+        assert_eq!(index.offset(&text, 2, 2), None);
+        assert_eq!(index.offset(&text, 3, 0), None);
+
+        assert_eq!(index.offset_utf16(&text, 1, 2), Some(9));
+        assert_eq!(index.offset_utf16(&text, 1, 1), None);
+        assert_eq!(index.offset_utf16(&text, 1, 3), Some(10));
+    }
+
+    #[test]
+    fn test_update() {
+        let edits = [
+            ("ab\ncd\nef", 1..4, "x"),
+            ("ab\ncd\nef", 2..2, "\n\r\n"),
+            ("ab\r", 3..3, "\ncd"),
+            ("ab\rcd", 3..3, "\n"),
+            ("ab\r\ncd", 3..4, ""),
+            ("a\nb\nc\nd\ne", 0..9, ""),
+            ("a\nb\nc\nd\ne", 4..4, "ö\r💻\n"),
+            ("\n\n\n\n", 1..3, "\r"),
+            ("", 0..0, "\r\n"),
+        ];
+
+        for (before, range, insert) in edits {
+            let mut index = LineIndex::new(&Ume8String::from(before));
+
+            let mut after = String::from(before);
+            after.replace_range(range.clone(), insert);
+            let after = Ume8String::from(after);
+
+            index.update(&after, range, insert.len());
+
+            assert_eq!(index, LineIndex::new(&after), "{:?}", before);
+        }
+    }
+
+    #[test]
+    fn test_update_all_ranges() {
+        let before = "a\r\n\rö\n\n\r💻";
+        let boundaries = before
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([before.len()])
+            .collect::<Vec<_>>();
+
+        for &start in &boundaries {
+            for &end in boundaries.iter().filter(|&&end| end >= start) {
+                for insert in ["", "\n", "\r", "x\r\ny", "ӕ\r"] {
+                    let mut index = LineIndex::new(&Ume8String::from(before));
+
+                    let mut after = String::from(before);
+                    after.replace_range(start..end, insert);
+                    let after = Ume8String::from(after);
+
+                    index.update(&after, start..end, insert.len());
+
+                    assert_eq!(index, LineIndex::new(&after));
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,47 @@
+mod canonical;
+mod char_ext;
+mod cursor;
 mod decode;
 mod encode;
+mod escape;
+mod indexed;
+mod iter_ext;
+mod line_index;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod parse;
+mod seek_reader;
+mod sequence;
+mod simd;
 mod str;
 mod string;
+mod table;
+mod transcode;
 mod util;
+mod validate;
+pub mod varint;
+pub mod wide;
 
+pub use self::canonical::*;
+pub use self::char_ext::*;
+pub use self::cursor::*;
 pub use self::decode::*;
 pub use self::encode::*;
+pub use self::escape::*;
+pub use self::indexed::*;
+pub use self::iter_ext::*;
+pub use self::line_index::*;
+#[cfg(feature = "rayon")]
+pub use self::parallel::*;
+pub use self::parse::*;
+pub use self::seek_reader::*;
+pub use self::sequence::*;
 pub use self::str::*;
 pub use self::string::*;
+pub use self::table::{classify, ByteKind};
+pub use self::transcode::*;
 pub use self::util::*;
+pub use self::validate::*;
 
 const MASK_SEQ: u8 = 0b10000000;
 const MASK_SEQ_START: u8 = 0b01000000;
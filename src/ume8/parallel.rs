@@ -0,0 +1,154 @@
+use crate::ume8::simd;
+use crate::ume8::str::Ume8Str;
+use crate::ume8::transcode;
+use crate::ume8::util::is_char_start;
+use crate::ume8::validate::{validate, Ume8Error};
+use rayon::prelude::*;
+
+const CHUNK_SIZE: usize = 1 << 16;
+
+// Chunks start at singleton or start bytes, so that no valid sequence is split. Invalid
+// data might contain long runs of other bytes, in which case the chunk grows until the
+// next singleton or start byte.
+fn chunk_bounds(bytes: &[u8], chunk_size: usize) -> Vec<usize> {
+    let mut bounds = vec![0];
+    let mut index = chunk_size;
+
+    while index < bytes.len() {
+        match bytes[index..].iter().position(is_char_start) {
+            Some(offset) => index += offset,
+            None => break,
+        }
+
+        bounds.push(index);
+        index += chunk_size;
+    }
+
+    bounds.push(bytes.len());
+    bounds
+}
+
+pub fn par_validate(bytes: &[u8]) -> Result<(), Ume8Error> {
+    par_validate_with_chunk_size(bytes, CHUNK_SIZE)
+}
+
+fn par_validate_with_chunk_size(bytes: &[u8], chunk_size: usize) -> Result<(), Ume8Error> {
+    let bounds = chunk_bounds(bytes, chunk_size);
+
+    let error = bounds
+        .par_windows(2)
+        .filter_map(|bounds| {
+            let (start, end) = (bounds[0], bounds[1]);
+            let error = validate(&bytes[start..end]).err()?;
+            let valid_up_to = start + error.valid_up_to();
+
+            // a sequence that is incomplete at the end of a chunk is interrupted by the
+            // singleton or start byte at the start of the next chunk
+            let error_len = match error.error_len() {
+                None if end < bytes.len() => Some(end - valid_up_to),
+                error_len => error_len,
+            };
+
+            Some(Ume8Error::new(valid_up_to, error_len))
+        })
+        .min_by_key(|error| error.valid_up_to());
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+pub fn par_count_chars(s: &Ume8Str) -> usize {
+    s.as_bytes()
+        .par_chunks(CHUNK_SIZE)
+        .map(simd::count_char_starts)
+        .sum()
+}
+
+pub fn par_transcode_to_utf8(s: &Ume8Str) -> String {
+    par_transcode_to_utf8_with_chunk_size(s, CHUNK_SIZE)
+}
+
+fn par_transcode_to_utf8_with_chunk_size(s: &Ume8Str, chunk_size: usize) -> String {
+    let src = s.as_bytes();
+    let bounds = chunk_bounds(src, chunk_size);
+    let mut dst = vec![0; src.len()];
+
+    let mut dst_chunks = Vec::with_capacity(bounds.len() - 1);
+    let mut dst_rest = dst.as_mut_slice();
+    for bounds in bounds.windows(2) {
+        let (dst_chunk, rest) = dst_rest.split_at_mut(bounds[1] - bounds[0]);
+        dst_chunks.push(dst_chunk);
+        dst_rest = rest;
+    }
+
+    let written_lens = dst_chunks
+        .into_par_iter()
+        .zip(bounds.par_windows(2))
+        .map(|(dst_chunk, bounds)| {
+            transcode::decode_ume8_to_utf8(&src[bounds[0]..bounds[1]], dst_chunk)
+        })
+        .collect::<Vec<_>>();
+
+    // chunks only shrink if they contain overlong sequences
+    let mut len = 0;
+    for (bounds, written_len) in bounds.windows(2).zip(written_lens) {
+        dst.copy_within(bounds[0]..bounds[0] + written_len, len);
+        len += written_len;
+    }
+    dst.truncate(len);
+
+    unsafe { String::from_utf8_unchecked(dst) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_validate() {
+        for bytes in super::super::simd::tests::samples() {
+            for chunk_size in [1, 2, 3, 5, 16, CHUNK_SIZE] {
+                assert_eq!(
+                    par_validate_with_chunk_size(&bytes, chunk_size),
+                    validate(&bytes)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_par_validate_incomplete_at_chunk_end() {
+        let bytes = [b'a', 0b11000001, 0b10000001, 0b11000001, 0b10100001];
+
+        assert_eq!(
+            par_validate_with_chunk_size(&bytes, 3),
+            Err(Ume8Error::new(1, Some(2)))
+        );
+    }
+
+    #[test]
+    fn test_par_count_chars() {
+        let string = crate::ume8::Ume8String::from("aöӕธ💻".repeat(50_000));
+
+        assert_eq!(par_count_chars(&string), 5 * 50_000);
+    }
+
+    #[test]
+    fn test_par_transcode_to_utf8() {
+        for bytes in super::super::simd::tests::samples() {
+            let s = match Ume8Str::from_bytes(&bytes) {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+
+            for chunk_size in [1, 2, 3, 5, 16, CHUNK_SIZE] {
+                assert_eq!(
+                    par_transcode_to_utf8_with_chunk_size(s, chunk_size),
+                    String::from(s)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::ume8::str::Ume8Str;
+use crate::ume8::string::Ume8String;
+use std::convert::Infallible;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, ParseFloatError, ParseIntError,
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Parsing from a [`Ume8Str`], used by [`Ume8Str::parse`].
+///
+/// Integers are parsed from the UME-8 bytes directly. Floats are ASCII whenever they are
+/// valid, so they are parsed by the standard library from the borrowed bytes. All other
+/// types use their `FromStr` implementation, which only needs a transcoded copy if the
+/// string is not ASCII.
+///
+/// Only types implementing this trait can be parsed with [`Ume8Str::parse`]. Any other
+/// `FromStr` type can be parsed with [`Ume8Str::parse_str`].
+pub trait Ume8FromStr: Sized {
+    type Err;
+
+    fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err>;
+}
+
+macro_rules! impl_via_from_str {
+    ($($ty:ty),+) => {$(
+        impl Ume8FromStr for $ty {
+            type Err = <$ty as FromStr>::Err;
+
+            #[inline]
+            fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+                s.parse_str()
+            }
+        }
+    )+};
+}
+
+impl_via_from_str!(
+    bool,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
+    PathBuf
+);
+
+macro_rules! impl_int {
+    (@parse $ty:ty, $digits:expr, $accumulate:ident) => {{
+        let mut value: $ty = 0;
+
+        for &byte in $digits {
+            let digit = byte.wrapping_sub(b'0');
+
+            if digit > 9 {
+                return None;
+            }
+
+            value = value.checked_mul(10)?.$accumulate(digit as $ty)?;
+        }
+
+        Some(value)
+    }};
+    (unsigned $($ty:ty),+) => {$(
+        impl Ume8FromStr for $ty {
+            type Err = ParseIntError;
+
+            fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+                let parse_fast = |bytes: &[u8]| -> Option<$ty> {
+                    let digits = match bytes {
+                        [b'+', digits @ ..] => digits,
+                        digits => digits,
+                    };
+
+                    if digits.is_empty() {
+                        return None;
+                    }
+
+                    impl_int!(@parse $ty, digits, checked_add)
+                };
+
+                match parse_fast(s.as_bytes()) {
+                    Some(value) => Ok(value),
+                    // slow path for the same error as `str::parse`
+                    None => s.parse_str(),
+                }
+            }
+        }
+    )+};
+    (signed $($ty:ty),+) => {$(
+        impl Ume8FromStr for $ty {
+            type Err = ParseIntError;
+
+            fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+                let parse_fast = |bytes: &[u8]| -> Option<$ty> {
+                    match bytes {
+                        [] | [b'+' | b'-'] => None,
+                        [b'-', digits @ ..] => impl_int!(@parse $ty, digits, checked_sub),
+                        [b'+', digits @ ..] | digits => impl_int!(@parse $ty, digits, checked_add),
+                    }
+                };
+
+                match parse_fast(s.as_bytes()) {
+                    Some(value) => Ok(value),
+                    // slow path for the same error as `str::parse`
+                    None => s.parse_str(),
+                }
+            }
+        }
+    )+};
+}
+
+impl_int!(unsigned u8, u16, u32, u64, u128, usize);
+impl_int!(signed i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_float {
+    ($($ty:ty),+) => {$(
+        impl Ume8FromStr for $ty {
+            type Err = ParseFloatError;
+
+            // Every valid float literal is ASCII, so the bytes are parsed without copying
+            // them and anything else fails without being transcoded.
+            fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+                match s.as_ascii_str() {
+                    Some(ascii) => ascii.parse(),
+                    // same error as `str::parse` for any non-empty invalid literal
+                    None => "\u{80}".parse(),
+                }
+            }
+        }
+    )+};
+}
+
+impl_float!(f32, f64);
+
+impl Ume8FromStr for char {
+    type Err = <char as FromStr>::Err;
+
+    fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Ok(ch),
+            _ => s.parse_str(),
+        }
+    }
+}
+
+impl Ume8FromStr for String {
+    type Err = Infallible;
+
+    fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl Ume8FromStr for Ume8String {
+    type Err = Infallible;
+
+    fn from_ume8_str(s: &Ume8Str) -> Result<Self, Self::Err> {
+        Ok(s.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<F: Ume8FromStr>(s: &str) -> Result<F, F::Err> {
+        Ume8String::from(s).parse()
+    }
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse::<u8>("255"), Ok(255));
+        assert_eq!(parse::<u8>("+0"), Ok(0));
+        assert_eq!(parse::<i8>("-128"), Ok(-128));
+        assert_eq!(parse::<i8>("+127"), Ok(127));
+        assert_eq!(parse::<i64>("-9223372036854775808"), Ok(i64::MIN));
+        assert_eq!(
+            parse::<u128>("340282366920938463463374607431768211455"),
+            Ok(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn test_parse_int_errors_match_str() {
+        for sample in ["", "+", "-", "-0", "256", "-1", "1a", "١", "💻", " 1"] {
+            assert_eq!(parse::<u8>(sample), sample.parse::<u8>());
+        }
+
+        for sample in ["", "+", "-", "128", "-129", "--1", "1ö", "-١"] {
+            assert_eq!(parse::<i8>(sample), sample.parse::<i8>());
+        }
+    }
+
+    #[test]
+    fn test_parse_float() {
+        for sample in ["1.5", "-0.25e3", "inf", "NaN", "1ö", "", "💻", "1e"] {
+            assert_eq!(parse::<f32>(sample).is_ok(), sample.parse::<f32>().is_ok());
+
+            let parsed = parse::<f64>(sample);
+            let expected = sample.parse::<f64>();
+
+            match (parsed, expected) {
+                (Ok(a), Ok(b)) => assert!(a == b || (a.is_nan() && b.is_nan())),
+                (a, b) => assert_eq!(a, b),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_other() {
+        assert_eq!(parse::<char>("ö"), Ok('ö'));
+        assert_eq!(parse::<char>("öö"), "öö".parse::<char>());
+        assert_eq!(parse::<bool>("true"), Ok(true));
+        assert_eq!(parse::<Ipv4Addr>("127.0.0.1"), Ok(Ipv4Addr::LOCALHOST));
+        assert_eq!(parse::<String>("aö💻"), Ok(String::from("aö💻")));
+        assert_eq!(parse::<Ume8String>("aö💻"), Ok(Ume8String::from("aö💻")));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Word(String);
+
+    impl FromStr for Word {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.chars().all(char::is_alphabetic) {
+                true => Ok(Word(s.to_owned())),
+                false => Err(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(Ume8String::from("abc").parse_str(), Ok(Word("abc".into())));
+        assert_eq!(Ume8String::from("aö").parse_str(), Ok(Word("aö".into())));
+        assert_eq!(Ume8String::from("a💻").parse_str::<Word>(), Err(()));
+        assert_eq!(Ume8String::from("12").parse_str::<u8>(), Ok(12));
+    }
+}
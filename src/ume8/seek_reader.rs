@@ -0,0 +1,218 @@
+use crate::ume8::string::Ume8String;
+use crate::ume8::util::{is_char_start, MAX_SEQUENCE_LEN};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Chars read by a [`Ume8SeekReader`], along with the aligned offset they start at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlignedChars {
+    pub offset: u64,
+    pub text: Ume8String,
+}
+
+/// Reads chars from arbitrary offsets of a seekable UME-8 stream.
+///
+/// Offsets that fall inside a sequence are moved back to the start of that sequence, and
+/// offsets past the end of the stream are moved to its end.
+#[derive(Debug)]
+pub struct Ume8SeekReader<R> {
+    inner: R,
+}
+
+impl<R: Read + Seek> Ume8SeekReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Current offset, which is always on a sequence boundary.
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.inner.stream_position()
+    }
+
+    /// Reads up to `n` chars from the current offset and moves past them.
+    pub fn read_chars(&mut self, n: usize) -> io::Result<AlignedChars> {
+        let offset = self.position()?;
+        self.read_chars_at(offset, n)
+    }
+
+    /// Reads up to `n` chars starting at `offset` and moves past them.
+    pub fn read_chars_at(&mut self, offset: u64, n: usize) -> io::Result<AlignedChars> {
+        let offset = self.align(offset)?;
+
+        self.inner.seek(SeekFrom::Start(offset))?;
+        // the buffer grows with the bytes read, so a large `n` is bounded by the stream
+        let limit = n.saturating_mul(MAX_SEQUENCE_LEN) as u64;
+        let mut bytes = Vec::new();
+        (&mut self.inner).take(limit).read_to_end(&mut bytes)?;
+
+        let end = bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| is_char_start(byte))
+            .nth(n)
+            .map_or(bytes.len(), |(index, _)| index);
+        bytes.truncate(end);
+
+        self.inner.seek(SeekFrom::Start(offset + end as u64))?;
+
+        Ok(AlignedChars {
+            offset,
+            text: into_string(bytes)?,
+        })
+    }
+
+    /// Reads up to `n` chars ending at `offset` and moves to the first of them.
+    pub fn read_chars_backward(&mut self, offset: u64, n: usize) -> io::Result<AlignedChars> {
+        let end = self.align(offset)?;
+        let start = end.saturating_sub(n.saturating_mul(MAX_SEQUENCE_LEN) as u64);
+
+        self.inner.seek(SeekFrom::Start(start))?;
+        let mut bytes = vec![0; (end - start) as usize];
+        self.inner.read_exact(&mut bytes)?;
+
+        let first = bytes
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, byte)| is_char_start(byte))
+            .take(n)
+            .last()
+            .map_or(bytes.len(), |(index, _)| index);
+        bytes.drain(..first);
+
+        let offset = start + first as u64;
+        self.inner.seek(SeekFrom::Start(offset))?;
+
+        Ok(AlignedChars {
+            offset,
+            text: into_string(bytes)?,
+        })
+    }
+
+    // Finds the start of the sequence containing `offset`.
+    fn align(&mut self, offset: u64) -> io::Result<u64> {
+        let len = self.inner.seek(SeekFrom::End(0))?;
+        if offset >= len {
+            return Ok(len);
+        }
+
+        let start = offset.saturating_sub(MAX_SEQUENCE_LEN as u64 - 1);
+        let mut window = [0; MAX_SEQUENCE_LEN];
+        let window = &mut window[..=(offset - start) as usize];
+        self.inner.seek(SeekFrom::Start(start))?;
+        self.inner.read_exact(window)?;
+
+        match window.iter().rposition(is_char_start) {
+            Some(index) => Ok(start + index as u64),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no UME-8 sequence boundary near offset {}", offset),
+            )),
+        }
+    }
+}
+
+/// Seeks to the start of the sequence containing the target offset and returns that
+/// aligned offset.
+impl<R: Read + Seek> Seek for Ume8SeekReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = self.inner.seek(pos)?;
+        let offset = self.align(target)?;
+        self.inner.seek(SeekFrom::Start(offset))
+    }
+}
+
+fn into_string(bytes: Vec<u8>) -> io::Result<Ume8String> {
+    Ume8String::from_bytes(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader() -> Ume8SeekReader<io::Cursor<Vec<u8>>> {
+        let text = Ume8String::from("aö💻 ӕb");
+        Ume8SeekReader::new(io::Cursor::new(text.into_bytes()))
+    }
+
+    fn aligned(offset: u64, text: &str) -> AlignedChars {
+        AlignedChars {
+            offset,
+            text: Ume8String::from(text),
+        }
+    }
+
+    #[test]
+    fn test_seek_aligns() {
+        let mut reader = reader();
+
+        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 1);
+        assert_eq!(reader.seek(SeekFrom::Start(6)).unwrap(), 3);
+        assert_eq!(reader.seek(SeekFrom::Start(7)).unwrap(), 7);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 10);
+        assert_eq!(reader.seek(SeekFrom::Start(100)).unwrap(), 11);
+        assert_eq!(reader.seek(SeekFrom::Current(-2)).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_read_chars_at() {
+        let mut reader = reader();
+
+        assert_eq!(reader.read_chars_at(5, 2).unwrap(), aligned(3, "💻 "));
+        assert_eq!(reader.position().unwrap(), 8);
+        assert_eq!(reader.read_chars(10).unwrap(), aligned(8, "ӕb"));
+        assert_eq!(reader.read_chars(1).unwrap(), aligned(11, ""));
+        assert_eq!(reader.read_chars_at(0, 0).unwrap(), aligned(0, ""));
+    }
+
+    #[test]
+    fn test_read_chars_large_n() {
+        let mut reader = reader();
+
+        assert_eq!(
+            reader.read_chars_at(3, usize::MAX).unwrap(),
+            aligned(3, "💻 ӕb")
+        );
+        assert_eq!(
+            reader.read_chars_at(0, usize::MAX / 8).unwrap(),
+            aligned(0, "aö💻 ӕb")
+        );
+        assert_eq!(reader.read_chars(usize::MAX / 8).unwrap(), aligned(11, ""));
+    }
+
+    #[test]
+    fn test_read_chars_backward() {
+        let mut reader = reader();
+
+        assert_eq!(reader.read_chars_backward(9, 2).unwrap(), aligned(3, "💻 "));
+        assert_eq!(reader.position().unwrap(), 3);
+        assert_eq!(reader.read_chars_backward(3, 10).unwrap(), aligned(0, "aö"));
+        assert_eq!(
+            reader.read_chars_backward(100, 1).unwrap(),
+            aligned(10, "b")
+        );
+        assert_eq!(reader.read_chars_backward(4, 0).unwrap(), aligned(3, ""));
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let mut reader = Ume8SeekReader::new(io::Cursor::new(vec![b'a', 0x80, 0x80, 0x80, 0x80]));
+
+        let error = reader.seek(SeekFrom::Start(4)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let error = reader.read_chars_at(0, 2).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::decode::DecodeUnchecked;
+use crate::ume8::util::is_sequence_end;
+
+/// A single encoded sequence of a [`Ume8Str`](crate::ume8::Ume8Str).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sequence<'a> {
+    bytes: &'a [u8],
+    value: char,
+}
+
+impl<'a> Sequence<'a> {
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn value(&self) -> char {
+        self.value
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Sequences are never empty, this only exists for consistency with `len`.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Whether the sequence uses the shortest possible encoding of its value, which is
+    /// always the case for valid UME-8.
+    pub fn is_canonical(&self) -> bool {
+        self.bytes.len() == self.value.len_ume8()
+    }
+}
+
+// Sequence at the start of `bytes`, which must be valid UME-8.
+pub(crate) fn first_sequence(bytes: &[u8]) -> Sequence<'_> {
+    let len = bytes.iter().position(is_sequence_end).unwrap() + 1;
+    let value = DecodeUnchecked::new(bytes[..len].iter().copied())
+        .next()
+        .unwrap();
+
+    Sequence {
+        bytes: &bytes[..len],
+        value: char::from_u32(value).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ume8::{classify, ByteKind, Ume8Str, Ume8String};
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(b'a'), ByteKind::Singleton);
+        assert_eq!(classify(0b11000001), ByteKind::Start);
+        assert_eq!(classify(0b10000001), ByteKind::Continuation);
+        assert_eq!(classify(0b10100001), ByteKind::End);
+    }
+
+    #[test]
+    fn test_sequence_at() {
+        let string = Ume8String::from("aö💻");
+
+        let sequence = string.sequence_at(3).unwrap();
+        assert_eq!(sequence.as_bytes(), &string.as_bytes()[3..7]);
+        assert_eq!(sequence.value(), '💻');
+        assert_eq!(sequence.len(), 4);
+        assert!(sequence.is_canonical());
+
+        assert_eq!(string.sequence_at(0).unwrap().value(), 'a');
+        assert_eq!(string.sequence_at(1).unwrap().len(), 2);
+        assert_eq!(string.sequence_at(2), None);
+        assert_eq!(string.sequence_at(7), None);
+    }
+
+    #[test]
+    fn test_sequence_overlong() {
+        assert!(Ume8Str::from_bytes(&[0b11000000, 0b10100001]).is_err());
+
+        let string = Ume8String::from("\u{7F}\u{80}\u{7FF}\u{800}\u{FFFF}\u{10000}");
+        let mut offset = 0;
+        while let Some(sequence) = string.sequence_at(offset) {
+            assert!(sequence.is_canonical());
+            offset += sequence.len();
+        }
+    }
+}
@@ -0,0 +1,529 @@
+// Vectorized helpers for scanning UME-8 bytes.
+//
+// On x86_64 the AVX2 or SSE2 implementation is picked at runtime, every other target uses
+// the portable word-at-a-time implementation.
+
+use crate::ume8::util::{is_char_start, is_sequence_end, MAX_SEQUENCE_LEN};
+
+const WORD_SIZE: usize = std::mem::size_of::<u64>();
+const WORD_HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+const WORD_LOW_BITS: u64 = 0x0101_0101_0101_0101;
+
+#[inline]
+fn read_word(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..WORD_SIZE].try_into().unwrap())
+}
+
+/// Length of the leading run of singleton (ASCII) bytes.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn ascii_len(bytes: &[u8]) -> usize {
+    if is_x86_feature_detected!("avx2") {
+        unsafe { x86::ascii_len_avx2(bytes) }
+    } else {
+        unsafe { x86::ascii_len_sse2(bytes) }
+    }
+}
+
+/// Length of the leading run of singleton (ASCII) bytes.
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn ascii_len(bytes: &[u8]) -> usize {
+    ascii_len_word(bytes)
+}
+
+/// Number of singleton and sequence start bytes, which equals the number of chars.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn count_char_starts(bytes: &[u8]) -> usize {
+    if is_x86_feature_detected!("avx2") {
+        unsafe { x86::count_char_starts_avx2(bytes) }
+    } else {
+        unsafe { x86::count_char_starts_sse2(bytes) }
+    }
+}
+
+/// Number of singleton and sequence start bytes, which equals the number of chars.
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn count_char_starts(bytes: &[u8]) -> usize {
+    count_char_starts_word(bytes)
+}
+
+/// End of the valid sequences from `start`, which is always a char boundary.
+///
+/// `start` must be the end of a valid sequence and at least 3, as every byte is checked
+/// against the three bytes before it. Sequences after the returned offset may still be
+/// valid, for example at the end of the input.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn valid_sequences_end(bytes: &[u8], start: usize) -> usize {
+    if is_x86_feature_detected!("avx2") {
+        unsafe { x86::valid_sequences_end_avx2(bytes, start) }
+    } else {
+        unsafe { x86::valid_sequences_end_sse2(bytes, start) }
+    }
+}
+
+/// End of the valid sequences from `start`, which is always a char boundary.
+///
+/// `start` must be the end of a valid sequence and at least 3, as every byte is checked
+/// against the three bytes before it. Sequences after the returned offset may still be
+/// valid, for example at the end of the input.
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn valid_sequences_end(bytes: &[u8], start: usize) -> usize {
+    valid_sequences_end_word(bytes, start)
+}
+
+pub(crate) fn ascii_len_scalar(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|byte| byte & 0b1000_0000 != 0)
+        .unwrap_or(bytes.len())
+}
+
+pub(crate) fn count_char_starts_scalar(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .filter(|&&byte| byte & 0b1100_0000 != 0b1000_0000)
+        .count()
+}
+
+// Start of the sequence left open by the bytes before `index`, or `index` if they end
+// with a complete sequence. Whether a sequence is complete is only checked by the byte
+// after it.
+fn close_sequences(bytes: &[u8], index: usize) -> usize {
+    if is_sequence_end(&bytes[index - 1]) {
+        return index;
+    }
+
+    let lower_bound = index.saturating_sub(MAX_SEQUENCE_LEN - 1);
+    bytes[lower_bound..index]
+        .iter()
+        .rposition(is_char_start)
+        .map_or(index, |position| lower_bound + position)
+}
+
+pub(crate) fn ascii_len_word(bytes: &[u8]) -> usize {
+    let mut index = 0;
+
+    while index + WORD_SIZE <= bytes.len() {
+        let high_bits = read_word(&bytes[index..]) & WORD_HIGH_BITS;
+
+        if high_bits != 0 {
+            return index + (high_bits.trailing_zeros() / 8) as usize;
+        }
+
+        index += WORD_SIZE;
+    }
+
+    index + ascii_len_scalar(&bytes[index..])
+}
+
+pub(crate) fn count_char_starts_word(bytes: &[u8]) -> usize {
+    let mut chunks = bytes.chunks_exact(WORD_SIZE);
+    let mut count = 0;
+
+    for chunk in &mut chunks {
+        let word = read_word(chunk);
+        // continuation and end bytes have the highest bit set and the second highest unset
+        let trailing = word & !(word << 1) & WORD_HIGH_BITS;
+        count += WORD_SIZE - trailing.count_ones() as usize;
+    }
+
+    count + count_char_starts_scalar(chunks.remainder())
+}
+
+// Highest bit of every byte of `word` whose lowest six bits are at least `min`, which
+// compares the data bits of start bytes. The sum never carries into the next byte.
+#[inline]
+fn data_at_least(word: u64, min: u8) -> u64 {
+    ((word & (0x3F * WORD_LOW_BITS)) + (0x80 - min as u64) * WORD_LOW_BITS) & WORD_HIGH_BITS
+}
+
+// Same as `x86::valid_sequences_end_sse2`, with a word at a time. The highest bit of
+// every byte marks where a check holds. `start` may also be inside a sequence, as long as
+// the bytes before it pass the same checks.
+pub(crate) fn valid_sequences_end_word(bytes: &[u8], start: usize) -> usize {
+    debug_assert!(start >= 3);
+
+    // bits 6 and 5 of every byte, moved to its highest bit
+    let is_start = |word: u64| word & (word << 1) & WORD_HIGH_BITS;
+    let is_inner = |word: u64| word & !(word << 1) & WORD_HIGH_BITS;
+    let is_cont = |word: u64| is_inner(word) & !(word << 2);
+    let mut index = start;
+
+    while index + WORD_SIZE <= bytes.len() {
+        let b0 = read_word(&bytes[index..]);
+        let b1 = read_word(&bytes[index - 1..]);
+        let b2 = read_word(&bytes[index - 2..]);
+        let b3 = read_word(&bytes[index - 3..]);
+
+        let (start1, start2, start3) = (is_start(b1), is_start(b2), is_start(b3));
+        let (cont0, cont1, cont2) = (is_cont(b0), is_cont(b1), is_cont(b2));
+        let end0 = is_inner(b0) & (b0 << 2);
+
+        let broken = (start1 | cont1) ^ is_inner(b0);
+        let too_long = cont0 & cont1 & cont2;
+
+        let invalid_2 = start1 & !data_at_least(b1, 0x04);
+        let surrogate = data_at_least(b2, 0x36) & !data_at_least(b2, 0x38);
+        let invalid_3 = cont1 & start2 & (!data_at_least(b2, 0x02) | surrogate);
+        let invalid_4 =
+            cont1 & cont2 & start3 & (!data_at_least(b3, 0x02) | data_at_least(b3, 0x22));
+        let invalid = end0 & (invalid_2 | invalid_3 | invalid_4);
+
+        if (broken | too_long | invalid) & WORD_HIGH_BITS != 0 {
+            break;
+        }
+
+        index += WORD_SIZE;
+    }
+
+    close_sequences(bytes, index)
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod x86 {
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn ascii_len_sse2(bytes: &[u8]) -> usize {
+        let mut index = 0;
+
+        while index + 16 <= bytes.len() {
+            let chunk = _mm_loadu_si128(bytes.as_ptr().add(index) as *const __m128i);
+            let high_bits = _mm_movemask_epi8(chunk) as u32;
+
+            if high_bits != 0 {
+                return index + high_bits.trailing_zeros() as usize;
+            }
+
+            index += 16;
+        }
+
+        index + super::ascii_len_word(&bytes[index..])
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn ascii_len_avx2(bytes: &[u8]) -> usize {
+        let mut index = 0;
+
+        while index + 32 <= bytes.len() {
+            let chunk = _mm256_loadu_si256(bytes.as_ptr().add(index) as *const __m256i);
+            let high_bits = _mm256_movemask_epi8(chunk) as u32;
+
+            if high_bits != 0 {
+                return index + high_bits.trailing_zeros() as usize;
+            }
+
+            index += 32;
+        }
+
+        index + super::ascii_len_word(&bytes[index..])
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn count_char_starts_sse2(bytes: &[u8]) -> usize {
+        // continuation and end bytes are the only bytes below -64 as `i8`
+        let threshold = _mm_set1_epi8(-64);
+        let mut index = 0;
+        let mut count = 0;
+
+        while index + 16 <= bytes.len() {
+            let chunk = _mm_loadu_si128(bytes.as_ptr().add(index) as *const __m128i);
+            let trailing = _mm_movemask_epi8(_mm_cmpgt_epi8(threshold, chunk)) as u32;
+            count += 16 - trailing.count_ones() as usize;
+            index += 16;
+        }
+
+        count + super::count_char_starts_word(&bytes[index..])
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn count_char_starts_avx2(bytes: &[u8]) -> usize {
+        // continuation and end bytes are the only bytes below -64 as `i8`
+        let threshold = _mm256_set1_epi8(-64);
+        let mut index = 0;
+        let mut count = 0;
+
+        while index + 32 <= bytes.len() {
+            let chunk = _mm256_loadu_si256(bytes.as_ptr().add(index) as *const __m256i);
+            let trailing = _mm256_movemask_epi8(_mm256_cmpgt_epi8(threshold, chunk)) as u32;
+            count += 32 - trailing.count_ones() as usize;
+            index += 32;
+        }
+
+        count + super::count_char_starts_word(&bytes[index..])
+    }
+
+    // Checks 16 bytes at a time against the three bytes before each of them:
+    //
+    // - a continuation or end byte follows exactly the start and continuation bytes
+    // - no sequence has more than two continuation bytes
+    // - the end byte of a sequence rejects overlong encodings, surrogates and values
+    //   above U+10FFFF, which only depend on the start byte
+    //
+    // Stops before the first block that breaks a rule, so that the scalar validation can
+    // report the exact error, and checks the bytes after the last block a word at a time.
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn valid_sequences_end_sse2(bytes: &[u8], start: usize) -> usize {
+        debug_assert!(start >= 3);
+
+        let mask_kind = _mm_set1_epi8(0b1100_0000u8 as i8);
+        let mask_cont_or_end = _mm_set1_epi8(0b1110_0000u8 as i8);
+        let inner = _mm_set1_epi8(0b1000_0000u8 as i8);
+        let end = _mm_set1_epi8(0b1010_0000u8 as i8);
+        // start bytes compared as `i8`
+        let min_start_2 = _mm_set1_epi8(0xC4u8 as i8);
+        let min_start_3_4 = _mm_set1_epi8(0xC2u8 as i8);
+        let max_start_4 = _mm_set1_epi8(0xE1u8 as i8);
+        let surrogate_mask = _mm_set1_epi8(0xFEu8 as i8);
+        let surrogate_start = _mm_set1_epi8(0xF6u8 as i8);
+        let mut index = start;
+
+        while index + 16 <= bytes.len() {
+            let ptr = bytes.as_ptr().add(index);
+            let b0 = _mm_loadu_si128(ptr as *const __m128i);
+            let b1 = _mm_loadu_si128(ptr.sub(1) as *const __m128i);
+            let b2 = _mm_loadu_si128(ptr.sub(2) as *const __m128i);
+            let b3 = _mm_loadu_si128(ptr.sub(3) as *const __m128i);
+
+            let is_start = |x| _mm_cmpeq_epi8(_mm_and_si128(x, mask_kind), mask_kind);
+            let is_inner = |x| _mm_cmpeq_epi8(_mm_and_si128(x, mask_kind), inner);
+            let is_cont = |x| _mm_cmpeq_epi8(_mm_and_si128(x, mask_cont_or_end), inner);
+
+            let (start1, start2, start3) = (is_start(b1), is_start(b2), is_start(b3));
+            let (cont0, cont1, cont2) = (is_cont(b0), is_cont(b1), is_cont(b2));
+            let end0 = _mm_cmpeq_epi8(_mm_and_si128(b0, mask_cont_or_end), end);
+
+            let broken = _mm_xor_si128(_mm_or_si128(start1, cont1), is_inner(b0));
+            let too_long = _mm_and_si128(_mm_and_si128(cont0, cont1), cont2);
+
+            let invalid_2 = _mm_and_si128(start1, _mm_cmplt_epi8(b1, min_start_2));
+            let invalid_3 = _mm_and_si128(
+                _mm_and_si128(cont1, start2),
+                _mm_or_si128(
+                    _mm_cmplt_epi8(b2, min_start_3_4),
+                    _mm_cmpeq_epi8(_mm_and_si128(b2, surrogate_mask), surrogate_start),
+                ),
+            );
+            let invalid_4 = _mm_and_si128(
+                _mm_and_si128(_mm_and_si128(cont1, cont2), start3),
+                _mm_or_si128(
+                    _mm_cmplt_epi8(b3, min_start_3_4),
+                    _mm_cmpgt_epi8(b3, max_start_4),
+                ),
+            );
+            let invalid = _mm_and_si128(
+                end0,
+                _mm_or_si128(_mm_or_si128(invalid_2, invalid_3), invalid_4),
+            );
+
+            let errors = _mm_or_si128(_mm_or_si128(broken, too_long), invalid);
+            if _mm_movemask_epi8(errors) != 0 {
+                return super::close_sequences(bytes, index);
+            }
+
+            index += 16;
+        }
+
+        super::valid_sequences_end_word(bytes, index)
+    }
+
+    // Same as `valid_sequences_end_sse2`, with 32 bytes at a time.
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn valid_sequences_end_avx2(bytes: &[u8], start: usize) -> usize {
+        debug_assert!(start >= 3);
+
+        let mask_kind = _mm256_set1_epi8(0b1100_0000u8 as i8);
+        let mask_cont_or_end = _mm256_set1_epi8(0b1110_0000u8 as i8);
+        let inner = _mm256_set1_epi8(0b1000_0000u8 as i8);
+        let end = _mm256_set1_epi8(0b1010_0000u8 as i8);
+        let min_start_2 = _mm256_set1_epi8(0xC4u8 as i8);
+        let min_start_3_4 = _mm256_set1_epi8(0xC2u8 as i8);
+        let max_start_4 = _mm256_set1_epi8(0xE1u8 as i8);
+        let surrogate_mask = _mm256_set1_epi8(0xFEu8 as i8);
+        let surrogate_start = _mm256_set1_epi8(0xF6u8 as i8);
+        let mut index = start;
+
+        while index + 32 <= bytes.len() {
+            let ptr = bytes.as_ptr().add(index);
+            let b0 = _mm256_loadu_si256(ptr as *const __m256i);
+            let b1 = _mm256_loadu_si256(ptr.sub(1) as *const __m256i);
+            let b2 = _mm256_loadu_si256(ptr.sub(2) as *const __m256i);
+            let b3 = _mm256_loadu_si256(ptr.sub(3) as *const __m256i);
+
+            let is_start = |x| _mm256_cmpeq_epi8(_mm256_and_si256(x, mask_kind), mask_kind);
+            let is_inner = |x| _mm256_cmpeq_epi8(_mm256_and_si256(x, mask_kind), inner);
+            let is_cont = |x| _mm256_cmpeq_epi8(_mm256_and_si256(x, mask_cont_or_end), inner);
+
+            let (start1, start2, start3) = (is_start(b1), is_start(b2), is_start(b3));
+            let (cont0, cont1, cont2) = (is_cont(b0), is_cont(b1), is_cont(b2));
+            let end0 = _mm256_cmpeq_epi8(_mm256_and_si256(b0, mask_cont_or_end), end);
+
+            let broken = _mm256_xor_si256(_mm256_or_si256(start1, cont1), is_inner(b0));
+            let too_long = _mm256_and_si256(_mm256_and_si256(cont0, cont1), cont2);
+
+            let invalid_2 = _mm256_and_si256(start1, _mm256_cmpgt_epi8(min_start_2, b1));
+            let invalid_3 = _mm256_and_si256(
+                _mm256_and_si256(cont1, start2),
+                _mm256_or_si256(
+                    _mm256_cmpgt_epi8(min_start_3_4, b2),
+                    _mm256_cmpeq_epi8(_mm256_and_si256(b2, surrogate_mask), surrogate_start),
+                ),
+            );
+            let invalid_4 = _mm256_and_si256(
+                _mm256_and_si256(_mm256_and_si256(cont1, cont2), start3),
+                _mm256_or_si256(
+                    _mm256_cmpgt_epi8(min_start_3_4, b3),
+                    _mm256_cmpgt_epi8(b3, max_start_4),
+                ),
+            );
+            let invalid = _mm256_and_si256(
+                end0,
+                _mm256_or_si256(_mm256_or_si256(invalid_2, invalid_3), invalid_4),
+            );
+
+            let errors = _mm256_or_si256(_mm256_or_si256(broken, too_long), invalid);
+            if _mm256_movemask_epi8(errors) != 0 {
+                return super::close_sequences(bytes, index);
+            }
+
+            index += 32;
+        }
+
+        super::valid_sequences_end_word(bytes, index)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // deterministic pseudo random data without pulling in a dependency
+    pub(crate) fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as u32
+        };
+
+        let mut bytes = Vec::with_capacity(len + 4);
+        while bytes.len() < len {
+            match next() % 4 {
+                // long ASCII runs
+                0 => bytes.extend((0..next() % 80).map(|_| (next() % 0x80) as u8)),
+                // random bytes
+                1 => bytes.push(next() as u8),
+                // valid chars
+                _ => {
+                    let ch = char::from_u32(next() % 0x11_0000).unwrap_or('\u{FFFD}');
+                    bytes.extend(crate::ume8::EncodeUnchecked::new([ch as u32].into_iter()));
+                }
+            }
+        }
+
+        bytes
+    }
+
+    pub(crate) fn samples() -> Vec<Vec<u8>> {
+        let mut samples = super::super::tests::data()
+            .into_iter()
+            .map(|(_, encoded)| encoded)
+            .collect::<Vec<_>>();
+
+        for seed in 0..200 {
+            let bytes = random_bytes(seed, seed as usize * 3);
+            // check every alignment of the vector loads
+            for offset in 0..4.min(bytes.len()) {
+                samples.push(bytes[offset..].to_vec());
+            }
+        }
+
+        // long valid text with a single corrupted byte at different positions
+        let text = samples.iter().flatten().cloned().collect::<Vec<u8>>();
+        for (seed, corruption) in random_bytes(1, 64).into_iter().enumerate() {
+            let mut bytes = text.clone();
+            bytes[seed * 97 % text.len()] = corruption;
+            samples.push(bytes);
+        }
+
+        samples.push(vec![b'a'; 1000]);
+        samples.push([vec![b'a'; 999], vec![0b11000000]].concat());
+
+        samples
+    }
+
+    #[test]
+    fn test_ascii_len() {
+        for bytes in samples() {
+            let expected = ascii_len_scalar(&bytes);
+
+            assert_eq!(ascii_len(&bytes), expected);
+            assert_eq!(ascii_len_word(&bytes), expected);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                assert_eq!(unsafe { x86::ascii_len_sse2(&bytes) }, expected);
+
+                if is_x86_feature_detected!("avx2") {
+                    assert_eq!(unsafe { x86::ascii_len_avx2(&bytes) }, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_char_starts() {
+        for bytes in samples() {
+            let expected = count_char_starts_scalar(&bytes);
+
+            assert_eq!(count_char_starts(&bytes), expected);
+            assert_eq!(count_char_starts_word(&bytes), expected);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                assert_eq!(unsafe { x86::count_char_starts_sse2(&bytes) }, expected);
+
+                if is_x86_feature_detected!("avx2") {
+                    assert_eq!(unsafe { x86::count_char_starts_avx2(&bytes) }, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_valid_sequences_end() {
+        use crate::ume8::validate::validate_scalar;
+
+        for bytes in samples() {
+            let Some(start) = (MAX_SEQUENCE_LEN - 1..bytes.len())
+                .find(|&index| is_char_start(&bytes[index]))
+                .filter(|&index| validate_scalar(&bytes[..index]).is_ok())
+            else {
+                continue;
+            };
+
+            let mut ends = vec![
+                valid_sequences_end(&bytes, start),
+                valid_sequences_end_word(&bytes, start),
+            ];
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                ends.push(unsafe { x86::valid_sequences_end_sse2(&bytes, start) });
+
+                if is_x86_feature_detected!("avx2") {
+                    ends.push(unsafe { x86::valid_sequences_end_avx2(&bytes, start) });
+                }
+            }
+
+            for end in ends {
+                assert!(end >= start);
+                assert_eq!(validate_scalar(&bytes[..end]), Ok(()));
+
+                if validate_scalar(&bytes).is_ok() {
+                    assert!(end + 32 + MAX_SEQUENCE_LEN > bytes.len());
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,84 @@
-use crate::ume8::decode::{DecodeUnchecked, ToCharUnchecked};
+use crate::ume8::char_ext::CharExt;
+use crate::ume8::decode::DecodeUnchecked;
+use crate::ume8::escape::{EscapeDebug, EscapeDefault, EscapeUnicode};
+use crate::ume8::parse::Ume8FromStr;
+use crate::ume8::sequence::{self, Sequence};
 use crate::ume8::string::Ume8String;
-use crate::ume8::util::is_singleton;
+use crate::ume8::util::{is_char_start, MAX_SEQUENCE_LEN};
+use crate::ume8::validate::{validate, Ume8Error};
+use crate::ume8::{canonical, simd, transcode};
 use std::fmt::{Debug, Display, Formatter};
-use std::iter::Cloned;
+use std::iter::FusedIterator;
+use std::num::NonZeroUsize;
+use std::ops::{Bound, Index, RangeBounds};
+use std::str::FromStr;
+
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Chars<'a> {
+    iter: std::slice::Iter<'a, u8>,
+}
+
+impl<'a> Chars<'a> {
+    pub fn as_str(&self) -> &'a Ume8Str {
+        unsafe { Ume8Str::from_inner(self.iter.as_slice()) }
+    }
+
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        DecodeUnchecked::new(self.iter.by_ref().copied()).advance_by(n)
+    }
+
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        DecodeUnchecked::new(self.iter.by_ref().copied()).advance_back_by(n)
+    }
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        DecodeUnchecked::new(self.iter.by_ref().copied())
+            .next()
+            .map(|data| char::from_u32(data).unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.iter.len();
+        (len.div_ceil(4), Some(len))
+    }
+
+    fn count(self) -> usize {
+        simd::count_char_starts(self.iter.as_slice())
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        DecodeUnchecked::new(self.iter.by_ref().copied())
+            .nth(n)
+            .map(|data| char::from_u32(data).unwrap())
+    }
+}
+
+impl DoubleEndedIterator for Chars<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        DecodeUnchecked::new(self.iter.by_ref().copied())
+            .next_back()
+            .map(|data| char::from_u32(data).unwrap())
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        DecodeUnchecked::new(self.iter.by_ref().copied())
+            .nth_back(n)
+            .map(|data| char::from_u32(data).unwrap())
+    }
+}
+
+impl FusedIterator for Chars<'_> {}
 
 #[repr(transparent)]
 #[derive(PartialOrd, PartialEq, Ord, Eq, Hash)]
@@ -15,6 +91,16 @@ impl Ume8Str {
         s.as_ref()
     }
 
+    pub fn from_bytes(bytes: &[u8]) -> Result<&Self, Ume8Error> {
+        validate(bytes)?;
+        Ok(unsafe { Self::from_inner(bytes) })
+    }
+
+    pub fn from_bytes_mut(bytes: &mut [u8]) -> Result<&mut Self, Ume8Error> {
+        validate(bytes)?;
+        Ok(unsafe { Self::from_inner_mut(bytes) })
+    }
+
     pub(crate) unsafe fn from_inner(inner: &[u8]) -> &Self {
         unsafe { std::mem::transmute(inner) }
     }
@@ -31,16 +117,170 @@ impl Ume8Str {
         self.bytes.is_empty()
     }
 
+    pub fn is_char_boundary(&self, index: usize) -> bool {
+        if index == 0 || index == self.bytes.len() {
+            return true;
+        }
+
+        match self.bytes.get(index) {
+            Some(byte) => is_char_start(byte),
+            None => false,
+        }
+    }
+
+    /// Largest char boundary at or before `index`, clamped to the length of the string.
+    pub fn floor_char_boundary(&self, index: usize) -> usize {
+        if index >= self.bytes.len() {
+            return self.bytes.len();
+        }
+
+        let lower_bound = index.saturating_sub(MAX_SEQUENCE_LEN - 1);
+        self.bytes[lower_bound..=index]
+            .iter()
+            .rposition(is_char_start)
+            .map_or(0, |position| lower_bound + position)
+    }
+
+    /// Smallest char boundary at or after `index`, clamped to the length of the string.
+    pub fn ceil_char_boundary(&self, index: usize) -> usize {
+        if index >= self.bytes.len() {
+            return self.bytes.len();
+        }
+
+        let upper_bound = usize::min(index + MAX_SEQUENCE_LEN, self.bytes.len());
+        self.bytes[index..upper_bound]
+            .iter()
+            .position(is_char_start)
+            .map_or(upper_bound, |position| index + position)
+    }
+
+    pub fn get<R: RangeBounds<usize>>(&self, range: R) -> Option<&Self> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.bytes.len(),
+        };
+
+        if start > end
+            || end > self.bytes.len()
+            || !self.is_char_boundary(start)
+            || !self.is_char_boundary(end)
+        {
+            return None;
+        }
+
+        Some(unsafe { Self::from_inner(&self.bytes[start..end]) })
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes
     }
 
+    /// # Safety
+    ///
+    /// The bytes must be valid UME-8 when the borrow ends. Use a safe in-place method such
+    /// as [`Ume8Str::map_chars_in_place`] where possible.
+    pub unsafe fn as_bytes_mut_unchecked(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+
+    /// Kept safe so that existing callers still compile while they migrate.
+    #[deprecated(
+        note = "unsound: writing invalid UME-8 through the returned bytes breaks the \
+                invariant that decoding relies on and can cause panics; use the unsafe \
+                `as_bytes_mut_unchecked` or a safe in-place method instead"
+    )]
     pub fn as_bytes_mut(&mut self) -> &mut [u8] {
         &mut self.bytes
     }
 
-    pub fn chars(&self) -> ToCharUnchecked<DecodeUnchecked<Cloned<std::slice::Iter<u8>>>> {
-        ToCharUnchecked::new(DecodeUnchecked::new(self.bytes.iter().cloned()))
+    // Bytes of multi-byte sequences are never ASCII, so only singletons are changed.
+    pub fn make_ascii_uppercase(&mut self) {
+        self.bytes.make_ascii_uppercase();
+    }
+
+    pub fn make_ascii_lowercase(&mut self) {
+        self.bytes.make_ascii_lowercase();
+    }
+
+    pub fn to_ascii_uppercase(&self) -> Ume8String {
+        let mut string = self.to_owned();
+        string.make_ascii_uppercase();
+        string
+    }
+
+    pub fn to_ascii_lowercase(&self) -> Ume8String {
+        let mut string = self.to_owned();
+        string.make_ascii_lowercase();
+        string
+    }
+
+    /// Replaces every occurrence of the ASCII char `from` with the ASCII char `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is not ASCII.
+    pub fn replace_ascii_byte(&mut self, from: u8, to: u8) {
+        assert!(
+            from.is_ascii() && to.is_ascii(),
+            "replace_ascii_byte: {:#04x} and {:#04x} must both be ASCII",
+            from,
+            to
+        );
+
+        for byte in self.bytes.iter_mut().filter(|byte| **byte == from) {
+            *byte = to;
+        }
+    }
+
+    /// Replaces every char with the result of `f`, which must be encoded with the same
+    /// number of bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns a char of a different length. All chars before it have been
+    /// replaced at that point.
+    pub fn map_chars_in_place<F: FnMut(char) -> char>(&mut self, mut f: F) {
+        let mut index = 0;
+
+        while index < self.bytes.len() {
+            let (ch, len) = char::decode_ume8(&self.bytes[index..]).unwrap();
+            let mapped = f(ch);
+
+            if mapped != ch {
+                assert!(
+                    mapped.len_ume8() == len,
+                    "map_chars_in_place: {:?} and {:?} have different lengths",
+                    ch,
+                    mapped
+                );
+
+                mapped.encode_ume8(&mut self.bytes[index..index + len]);
+            }
+
+            index += len;
+        }
+    }
+
+    pub fn chars(&self) -> Chars<'_> {
+        Chars {
+            iter: self.bytes.iter(),
+        }
+    }
+
+    /// Sequence starting at byte `offset`, or `None` if `offset` is not the start of a
+    /// sequence.
+    pub fn sequence_at(&self, offset: usize) -> Option<Sequence<'_>> {
+        if offset >= self.len() || !self.is_char_boundary(offset) {
+            return None;
+        }
+
+        Some(sequence::first_sequence(&self.bytes[offset..]))
     }
 
     // TODO
@@ -86,13 +326,100 @@ impl Ume8Str {
     //
     // }
 
-    // TODO
-    // pub fn parse(&self) -> T {
-    //
-    // }
+    pub fn parse<F: Ume8FromStr>(&self) -> Result<F, F::Err> {
+        F::from_ume8_str(self)
+    }
+
+    /// Parses any `FromStr` type from the string, borrowing it as a `&str` if it is ASCII
+    /// and transcoding it to UTF-8 otherwise.
+    pub fn parse_str<F: FromStr>(&self) -> Result<F, F::Err> {
+        if let Some(ascii) = self.as_ascii_str() {
+            return ascii.parse();
+        }
+
+        String::from(self).parse()
+    }
+
+    pub fn char_to_byte(&self, char_index: usize) -> Option<usize> {
+        let mut chars = self.chars();
+        chars.advance_by(char_index).ok()?;
+        Some(self.bytes.len() - chars.as_str().len())
+    }
+
+    pub fn byte_to_char(&self, byte_index: usize) -> Option<usize> {
+        if !self.is_char_boundary(byte_index) {
+            return None;
+        }
+
+        Some(simd::count_char_starts(&self.bytes[..byte_index]))
+    }
 
+    pub fn char_at(&self, char_index: usize) -> Option<char> {
+        self.chars().nth(char_index)
+    }
+
+    pub fn char_slice<R: RangeBounds<usize>>(&self, range: R) -> Option<&Self> {
+        let start_char = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end_char = match range.end_bound() {
+            Bound::Included(&end) => Some(end.checked_add(1)?),
+            Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => None,
+        };
+
+        let start = self.char_to_byte(start_char)?;
+        let rest = unsafe { Self::from_inner(&self.bytes[start..]) };
+        let end = match end_char {
+            Some(end_char) => start + rest.char_to_byte(end_char.checked_sub(start_char)?)?,
+            None => self.bytes.len(),
+        };
+
+        Some(unsafe { Self::from_inner(&self.bytes[start..end]) })
+    }
+
+    /// Whether all chars are ASCII, which is encoded identically in UME-8 and UTF-8.
     pub fn is_ascii(&self) -> bool {
-        self.bytes.iter().all(is_singleton)
+        simd::ascii_len(&self.bytes) == self.bytes.len()
+    }
+
+    pub fn as_ascii_str(&self) -> Option<&str> {
+        if !self.is_ascii() {
+            return None;
+        }
+
+        Some(unsafe { std::str::from_utf8_unchecked(&self.bytes) })
+    }
+
+    pub fn from_ascii(s: &str) -> Option<&Self> {
+        if !s.is_ascii() {
+            return None;
+        }
+
+        Some(unsafe { Self::from_inner(s.as_bytes()) })
+    }
+
+    /// Whether every sequence encodes a Unicode scalar value in the shortest possible form,
+    /// so surrogates and values above U+10FFFF are rejected as well.
+    ///
+    /// Validation rejects all of these, so this only fails for strings created from
+    /// unchecked bytes.
+    pub fn is_canonical(&self) -> bool {
+        canonical::is_canonical(&self.bytes)
+    }
+
+    pub fn escape_debug(&self) -> EscapeDebug<'_> {
+        EscapeDebug::new(self.chars())
+    }
+
+    pub fn escape_default(&self) -> EscapeDefault<'_> {
+        EscapeDefault::new(self.chars())
+    }
+
+    pub fn escape_unicode(&self) -> EscapeUnicode<'_> {
+        EscapeUnicode::new(self.chars())
     }
 }
 
@@ -102,6 +429,24 @@ impl Default for &Ume8Str {
     }
 }
 
+impl<R> Index<R> for Ume8Str
+where
+    R: RangeBounds<usize> + Debug,
+{
+    type Output = Ume8Str;
+
+    fn index(&self, index: R) -> &Self::Output {
+        match self.get((index.start_bound(), index.end_bound())) {
+            Some(s) => s,
+            None => panic!(
+                "byte range {:?} is out of bounds or not on a char boundary of a string with length {}",
+                index,
+                self.len()
+            ),
+        }
+    }
+}
+
 impl Display for Ume8Str {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let string: String = self.into();
@@ -124,6 +469,12 @@ impl ToOwned for Ume8Str {
     }
 }
 
+impl From<&Ume8Str> for Box<Ume8Str> {
+    fn from(s: &Ume8Str) -> Box<Ume8Str> {
+        s.to_owned().into_boxed_str()
+    }
+}
+
 impl AsRef<Ume8Str> for Ume8Str {
     fn as_ref(&self) -> &Ume8Str {
         self
@@ -138,8 +489,6 @@ impl AsRef<[u8]> for Ume8Str {
 
 impl From<&Ume8Str> for String {
     fn from(s: &Ume8Str) -> String {
-        let mut string = String::with_capacity(s.len());
-        string.extend(s.chars());
-        string
+        unsafe { String::from_utf8_unchecked(transcode::ume8_to_utf8_vec(&s.bytes)) }
     }
 }
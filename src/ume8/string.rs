@@ -1,8 +1,13 @@
+use crate::ume8::char_ext::CharExt;
 use crate::ume8::encode::EncodeUnchecked;
 use crate::ume8::str::Ume8Str;
-use std::borrow::{Borrow, BorrowMut};
+use crate::ume8::transcode;
+use crate::ume8::util::MAX_SEQUENCE_LEN;
+use crate::ume8::validate::{validate, FromUme8Error};
+use std::borrow::{Borrow, BorrowMut, Cow};
 use std::fmt::{Debug, Display, Formatter};
-use std::ops::{Deref, DerefMut, Index, IndexMut, RangeFull};
+use std::io;
+use std::ops::{Add, AddAssign, Bound, Deref, DerefMut, Index, IndexMut, RangeBounds, RangeFull};
 use std::str::FromStr;
 use std::string::String;
 
@@ -23,6 +28,16 @@ impl Ume8String {
         }
     }
 
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, FromUme8Error> {
+        match validate(&bytes) {
+            Ok(()) => Ok(Self { bytes }),
+            Err(error) => Err(FromUme8Error { bytes, error }),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` must contain valid UME-8.
     pub unsafe fn from_bytes_unchecked(bytes: Vec<u8>) -> Self {
         Self { bytes }
     }
@@ -31,6 +46,34 @@ impl Ume8String {
         self.bytes
     }
 
+    /// Converts a `String` into a `Ume8String` without allocating.
+    ///
+    /// Every char is encoded with the same number of bytes in UTF-8 and UME-8, so byte
+    /// offsets and lengths computed on the `String` are valid on the `Ume8String` and
+    /// vice versa.
+    pub fn from_utf8_string(string: String) -> Self {
+        let mut bytes = string.into_bytes();
+        transcode::utf8_to_ume8_in_place(&mut bytes);
+        Self { bytes }
+    }
+
+    /// Converts the `Ume8String` into a `String` without allocating.
+    ///
+    /// Byte offsets are preserved, see [`Ume8String::from_utf8_string`].
+    pub fn into_utf8_string(mut self) -> String {
+        let len = transcode::ume8_to_utf8_in_place(&mut self.bytes);
+        self.bytes.truncate(len);
+        unsafe { String::from_utf8_unchecked(self.bytes) }
+    }
+
+    pub fn into_ascii_string(self) -> Result<String, Self> {
+        if !self.is_ascii() {
+            return Err(self);
+        }
+
+        Ok(unsafe { String::from_utf8_unchecked(self.bytes) })
+    }
+
     pub fn capacity(&self) -> usize {
         self.bytes.capacity()
     }
@@ -53,27 +96,73 @@ impl Ume8String {
 
     pub fn push(&mut self, ch: char) {
         self.bytes
-            .extend(EncodeUnchecked::new([ch as u32].into_iter()));
+            .extend_from_slice(ch.encode_ume8(&mut [0; 4]).as_bytes());
     }
 
     pub fn push_string(&mut self, string: Self) {
         self.bytes.extend(string.bytes);
     }
 
-    // TODO
-    // pub fn truncate(&mut self, length: usize) {
-    //
-    // }
+    pub fn push_str(&mut self, string: &Ume8Str) {
+        self.bytes.extend_from_slice(string.as_bytes());
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len <= self.len() {
+            assert!(self.is_char_boundary(new_len));
+            self.bytes.truncate(new_len);
+        }
+    }
+
+    /// Shortens the string to at most `max_bytes` bytes without splitting a sequence.
+    pub fn truncate_to_byte_limit(&mut self, max_bytes: usize) {
+        let new_len = self.floor_char_boundary(max_bytes);
+        self.bytes.truncate(new_len);
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        let new_len = self.len() - ch.len_ume8();
+        self.bytes.truncate(new_len);
+        Some(ch)
+    }
+
+    pub fn remove(&mut self, index: usize) -> char {
+        let ch = match self[index..].chars().next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+
+        self.bytes.drain(index..index + ch.len_ume8());
+        ch
+    }
+
+    pub fn insert(&mut self, index: usize, ch: char) {
+        assert!(self.is_char_boundary(index));
+        self.insert_str(index, ch.encode_ume8(&mut [0; 4]));
+    }
+
+    pub fn insert_str(&mut self, index: usize, string: &Ume8Str) {
+        assert!(self.is_char_boundary(index));
+        self.bytes
+            .splice(index..index, string.as_bytes().iter().cloned());
+    }
 
-    // TODO
-    // pub fn pop(&mut self) -> Option<char> {
-    //     let ch = self.chars().rev().next()?;
-    //     let newlen = self.len() - ch.len_utf8();
-    //     unsafe {
-    //         self.vec.set_len(newlen);
-    //     }
-    //     Some(ch)
-    // }
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &Ume8Str) {
+        match range.start_bound() {
+            Bound::Included(&start) => assert!(self.is_char_boundary(start)),
+            Bound::Excluded(&start) => assert!(self.is_char_boundary(start + 1)),
+            Bound::Unbounded => {}
+        };
+        match range.end_bound() {
+            Bound::Included(&end) => assert!(self.is_char_boundary(end + 1)),
+            Bound::Excluded(&end) => assert!(self.is_char_boundary(end)),
+            Bound::Unbounded => {}
+        };
+
+        self.bytes
+            .splice(range, replace_with.as_bytes().iter().cloned());
+    }
 
     pub fn clear(&mut self) {
         self.bytes.clear();
@@ -90,13 +179,18 @@ impl Ume8String {
     pub fn as_mut_str(&mut self) -> &mut Ume8Str {
         self
     }
+
+    pub fn into_boxed_str(self) -> Box<Ume8Str> {
+        let bytes = Box::into_raw(self.bytes.into_boxed_slice());
+        unsafe { Box::from_raw(bytes as *mut Ume8Str) }
+    }
 }
 
 impl Deref for Ume8String {
     type Target = Ume8Str;
 
     fn deref(&self) -> &Self::Target {
-        &self[..]
+        unsafe { Ume8Str::from_inner(&self.bytes) }
     }
 }
 
@@ -136,11 +230,14 @@ impl BorrowMut<Ume8Str> for Ume8String {
     }
 }
 
-impl Index<RangeFull> for Ume8String {
+impl<R> Index<R> for Ume8String
+where
+    R: RangeBounds<usize> + Debug,
+{
     type Output = Ume8Str;
 
-    fn index(&self, _index: RangeFull) -> &Self::Output {
-        unsafe { Ume8Str::from_inner(&self.bytes) }
+    fn index(&self, index: R) -> &Self::Output {
+        &self.as_str()[index]
     }
 }
 
@@ -180,6 +277,145 @@ impl Debug for Ume8String {
     }
 }
 
+impl std::fmt::Write for Ume8String {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.bytes.reserve(s.len());
+        self.bytes
+            .extend(EncodeUnchecked::new(s.chars().map(|data| data as u32)));
+        Ok(())
+    }
+
+    fn write_char(&mut self, ch: char) -> std::fmt::Result {
+        self.push(ch);
+        Ok(())
+    }
+}
+
+/// Appends UTF-8 written through [`io::Write`] to a [`Ume8String`] as UME-8.
+///
+/// A char may be split across calls to `write`, so the bytes of an incomplete char are
+/// kept until the next call. Invalid UTF-8 is rejected when it is written, and an
+/// incomplete char left at the end is rejected by `flush`.
+///
+/// # Panics
+///
+/// Dropping the writer panics if it still holds an incomplete char, unless the thread is
+/// already panicking. Call `flush` before to handle it as an error instead.
+pub struct Ume8Writer<'a> {
+    string: &'a mut Ume8String,
+    pending: [u8; MAX_SEQUENCE_LEN],
+    pending_len: usize,
+}
+
+impl<'a> Ume8Writer<'a> {
+    pub fn new(string: &'a mut Ume8String) -> Self {
+        Self {
+            string,
+            pending: [0; MAX_SEQUENCE_LEN],
+            pending_len: 0,
+        }
+    }
+
+    fn push_utf8(string: &mut Ume8String, s: &str) {
+        std::fmt::Write::write_str(string, s).unwrap();
+    }
+}
+
+impl io::Write for Ume8Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut index = 0;
+
+        // complete the char left by the previous call one byte at a time, as its length
+        // is only known once it is valid
+        while self.pending_len > 0 && index < buf.len() {
+            self.pending[self.pending_len] = buf[index];
+            self.pending_len += 1;
+            index += 1;
+
+            match std::str::from_utf8(&self.pending[..self.pending_len]) {
+                Ok(s) => {
+                    Self::push_utf8(self.string, s);
+                    self.pending_len = 0;
+                }
+                Err(error) if error.error_len().is_none() => {}
+                Err(error) => {
+                    self.pending_len = 0;
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+                }
+            }
+        }
+
+        let rest = &buf[index..];
+        match std::str::from_utf8(rest) {
+            Ok(s) => Self::push_utf8(self.string, s),
+            Err(error) => {
+                let valid = &rest[..error.valid_up_to()];
+                Self::push_utf8(self.string, std::str::from_utf8(valid).unwrap());
+
+                if error.error_len().is_some() {
+                    // report the error on the next call if anything was written
+                    return match index + valid.len() {
+                        0 => Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+                        len => Ok(len),
+                    };
+                }
+
+                let incomplete = &rest[valid.len()..];
+                self.pending[..incomplete.len()].copy_from_slice(incomplete);
+                self.pending_len = incomplete.len();
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending_len > 0 {
+            self.pending_len = 0;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete UTF-8 sequence at the end of the written bytes",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Ume8Writer<'_> {
+    fn drop(&mut self) {
+        if self.pending_len > 0 && !std::thread::panicking() {
+            panic!("incomplete UTF-8 sequence at the end of the written bytes");
+        }
+    }
+}
+
+/// Creates a [`Ume8String`] using interpolation of runtime expressions, like `format!`.
+#[macro_export]
+macro_rules! ume8_format {
+    ($($arg:tt)*) => {{
+        let mut string = $crate::ume8::Ume8String::new();
+        ::std::fmt::Write::write_fmt(&mut string, ::std::format_args!($($arg)*))
+            .expect("a formatting trait implementation returned an error");
+        string
+    }};
+}
+
+impl Add<&Ume8Str> for Ume8String {
+    type Output = Ume8String;
+
+    fn add(mut self, other: &Ume8Str) -> Self::Output {
+        self.push_str(other);
+        self
+    }
+}
+
+impl AddAssign<&Ume8Str> for Ume8String {
+    fn add_assign(&mut self, other: &Ume8Str) {
+        self.push_str(other);
+    }
+}
+
 impl Extend<Ume8String> for Ume8String {
     fn extend<T: IntoIterator<Item = Ume8String>>(&mut self, iter: T) {
         iter.into_iter()
@@ -194,19 +430,50 @@ impl<'a> Extend<&'a Ume8String> for Ume8String {
     }
 }
 
+impl<'a> Extend<&'a Ume8Str> for Ume8String {
+    fn extend<T: IntoIterator<Item = &'a Ume8Str>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|s| self.push_str(s));
+    }
+}
+
+impl Extend<Box<Ume8Str>> for Ume8String {
+    fn extend<T: IntoIterator<Item = Box<Ume8Str>>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|s| self.push_str(&s));
+    }
+}
+
+impl<'a> Extend<Cow<'a, Ume8Str>> for Ume8String {
+    fn extend<T: IntoIterator<Item = Cow<'a, Ume8Str>>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|s| self.push_str(&s));
+    }
+}
+
+impl<'a> Extend<&'a str> for Ume8String {
+    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
+        iter.into_iter()
+            .for_each(|s| std::fmt::Write::write_str(self, s).unwrap());
+    }
+}
+
+impl Extend<String> for Ume8String {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, iter: T) {
+        iter.into_iter()
+            .for_each(|s| std::fmt::Write::write_str(self, &s).unwrap());
+    }
+}
+
 impl Extend<char> for Ume8String {
     fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
-        self.bytes.extend(EncodeUnchecked::new(
-            iter.into_iter().map(|data| data as u32),
-        ));
+        let iter = iter.into_iter();
+        self.bytes.reserve(iter.size_hint().0);
+        self.bytes
+            .extend(EncodeUnchecked::new(iter.map(|data| data as u32)));
     }
 }
 
 impl<'a> Extend<&'a char> for Ume8String {
     fn extend<T: IntoIterator<Item = &'a char>>(&mut self, iter: T) {
-        self.bytes.extend(EncodeUnchecked::new(
-            iter.into_iter().map(|data| *data as u32),
-        ));
+        self.extend(iter.into_iter().cloned());
     }
 }
 
@@ -221,6 +488,19 @@ where
     }
 }
 
+impl From<Box<Ume8Str>> for Ume8String {
+    fn from(s: Box<Ume8Str>) -> Ume8String {
+        let bytes = Box::into_raw(s) as *mut [u8];
+        unsafe { Ume8String::from_bytes_unchecked(Box::from_raw(bytes).into_vec()) }
+    }
+}
+
+impl From<Ume8String> for Box<Ume8Str> {
+    fn from(s: Ume8String) -> Box<Ume8Str> {
+        s.into_boxed_str()
+    }
+}
+
 impl From<&Ume8Str> for Ume8String {
     fn from(s: &Ume8Str) -> Ume8String {
         s.to_owned()
@@ -237,15 +517,15 @@ impl FromStr for Ume8String {
 
 impl From<&str> for Ume8String {
     fn from(s: &str) -> Ume8String {
-        let mut string = Ume8String::with_capacity(s.len());
-        string.extend(s.chars());
-        string
+        Ume8String {
+            bytes: transcode::utf8_to_ume8_vec(s.as_bytes()),
+        }
     }
 }
 
 impl From<String> for Ume8String {
     fn from(s: String) -> Ume8String {
-        s.as_str().into()
+        Ume8String::from_utf8_string(s)
     }
 }
 
@@ -257,7 +537,7 @@ impl From<&String> for Ume8String {
 
 impl From<char> for Ume8String {
     fn from(char: char) -> Ume8String {
-        let mut string = Ume8String::new();
+        let mut string = Ume8String::with_capacity(char.len_ume8());
         string.push(char);
         string
     }
@@ -271,7 +551,7 @@ impl From<&char> for Ume8String {
 
 impl From<Ume8String> for String {
     fn from(s: Ume8String) -> String {
-        s.as_str().into()
+        s.into_utf8_string()
     }
 }
 
@@ -280,3 +560,361 @@ impl From<&Ume8String> for String {
         s.as_str().into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::validate;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_fmt_write() {
+        let mut string = Ume8String::from("a");
+        std::fmt::Write::write_fmt(&mut string, format_args!("ö{}💻{:?}", 'ӕ', "ธ")).unwrap();
+
+        assert_eq!(string, Ume8String::from("aöӕ💻\"ธ\""));
+    }
+
+    #[test]
+    fn test_writer_single_bytes() {
+        let data = "aöӕธ💻".as_bytes();
+        let mut string = Ume8String::from("b");
+        let mut writer = Ume8Writer::new(&mut string);
+
+        for byte in data {
+            assert_eq!(io::Write::write(&mut writer, &[*byte]).unwrap(), 1);
+        }
+        io::Write::flush(&mut writer).unwrap();
+        drop(writer);
+
+        assert_eq!(string, Ume8String::from("baöӕธ💻"));
+    }
+
+    #[test]
+    fn test_writer_invalid() {
+        let mut string = Ume8String::new();
+        let mut writer = Ume8Writer::new(&mut string);
+
+        assert_eq!(io::Write::write(&mut writer, &[b'a', 0xFF]).unwrap(), 1);
+        assert!(io::Write::write(&mut writer, &[0xFF]).is_err());
+        assert!(io::Write::write_all(&mut writer, &[0xF0, 0x9F, b'a']).is_err());
+        assert!(io::Write::write_all(&mut writer, &[0xF0, 0x9F]).is_ok());
+        assert!(io::Write::flush(&mut writer).is_err());
+        drop(writer);
+
+        assert_eq!(string, Ume8String::from("a"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_writer_drop_incomplete() {
+        let mut string = Ume8String::new();
+        let mut writer = Ume8Writer::new(&mut string);
+
+        io::Write::write_all(&mut writer, &[0xF0, 0x9F]).unwrap();
+    }
+
+    #[test]
+    fn test_push_pop_truncate() {
+        let mut string = Ume8String::new();
+        string.push_str(&Ume8String::from("aö"));
+        string.push('💻');
+
+        assert_eq!(string.pop(), Some('💻'));
+        assert_eq!(string.pop(), Some('ö'));
+
+        string.push_str(&Ume8String::from("ӕธ"));
+        string.truncate(3);
+
+        assert_eq!(string, Ume8String::from("aӕ"));
+        assert_eq!(string.pop(), Some('ӕ'));
+        assert_eq!(string.pop(), Some('a'));
+        assert_eq!(string.pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_truncate_inside_char() {
+        let mut string = Ume8String::from("aö");
+        string.truncate(2);
+    }
+
+    #[test]
+    fn test_char_boundary_rounding() {
+        let string = Ume8String::from("aö💻");
+        let floors = [0, 1, 1, 3, 3, 3, 3, 7, 7];
+        let ceils = [0, 1, 3, 3, 7, 7, 7, 7, 7];
+
+        for index in 0..=8 {
+            assert_eq!(string.floor_char_boundary(index), floors[index]);
+            assert_eq!(string.ceil_char_boundary(index), ceils[index]);
+        }
+    }
+
+    #[test]
+    fn test_truncate_to_byte_limit() {
+        let original = Ume8String::from("aö💻");
+
+        for limit in 0..=8 {
+            let mut string = original.clone();
+            string.truncate_to_byte_limit(limit);
+
+            assert!(string.len() <= limit);
+            assert_eq!(string.len(), original.floor_char_boundary(limit));
+            assert!(Ume8Str::from_bytes(string.as_bytes()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_ascii_case_in_place() {
+        let mut string = Ume8String::from("aÖ💻b-Ä");
+        string.make_ascii_uppercase();
+        assert_eq!(string, Ume8String::from("AÖ💻B-Ä"));
+
+        string.make_ascii_lowercase();
+        assert_eq!(string, Ume8String::from("aÖ💻b-Ä"));
+
+        assert_eq!(string.to_ascii_uppercase(), Ume8String::from("AÖ💻B-Ä"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_bytes_mut() {
+        let mut string = Ume8String::from("aö");
+        unsafe { string.as_bytes_mut_unchecked()[0] = b'b' };
+        string.as_bytes_mut()[0] = b'c';
+
+        assert_eq!(string, Ume8String::from("cö"));
+    }
+
+    #[test]
+    fn test_replace_ascii_byte() {
+        let mut string = Ume8String::from("a/ö/💻");
+        string.replace_ascii_byte(b'/', b'\\');
+
+        assert_eq!(string, Ume8String::from("a\\ö\\💻"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replace_ascii_byte_non_ascii() {
+        Ume8String::from("a").replace_ascii_byte(b'a', 0xA0);
+    }
+
+    #[test]
+    fn test_map_chars_in_place() {
+        let mut string = Ume8String::from("aöß💻");
+        string.map_chars_in_place(|ch| match ch {
+            'ö' => 'Ö',
+            '💻' => '🖥',
+            ch => ch.to_ascii_uppercase(),
+        });
+
+        assert_eq!(string, Ume8String::from("AÖß🖥"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_map_chars_in_place_length_change() {
+        Ume8String::from("aö").map_chars_in_place(|_| 'ö');
+    }
+
+    #[test]
+    fn test_in_place_mutations_stay_valid() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut string = Ume8String::from("a/ö/💻");
+        let result = catch_unwind(AssertUnwindSafe(|| string.replace_ascii_byte(b'/', 0xC3)));
+        assert!(result.is_err());
+        assert_eq!(string, Ume8String::from("a/ö/💻"));
+
+        let mut string = Ume8String::from("aöb💻");
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            string.map_chars_in_place(|ch| match ch {
+                'ö' => 'Ö',
+                'b' => 'ӕ',
+                ch => ch,
+            })
+        }));
+        assert!(result.is_err());
+        assert!(validate(string.as_bytes()).is_ok());
+        assert_eq!(string, Ume8String::from("aÖb💻"));
+
+        let mut string = Ume8String::from("💻");
+        let result = catch_unwind(AssertUnwindSafe(|| string.map_chars_in_place(|_| 'a')));
+        assert!(result.is_err());
+        assert!(validate(string.as_bytes()).is_ok());
+        assert_eq!(string, Ume8String::from("💻"));
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut string = Ume8String::from("aö💻");
+        string.insert(1, 'ӕ');
+        string.insert_str(3, &Ume8String::from("xธ"));
+
+        assert_eq!(string, Ume8String::from("aӕxธö💻"));
+        assert_eq!(string.remove(4), 'ธ');
+        assert_eq!(string.remove(1), 'ӕ');
+        assert_eq!(string, Ume8String::from("axö💻"));
+    }
+
+    #[test]
+    fn test_index_range() {
+        let string = Ume8String::from("aöӕ");
+
+        assert_eq!(&string[1..3], Ume8String::from("ö").as_str());
+        assert_eq!(&string[3..], Ume8String::from("ӕ").as_str());
+        assert_eq!(&string[..=2], Ume8String::from("aö").as_str());
+        assert_eq!(string.get(2..), None);
+        assert_eq!(string.get(..7), None);
+    }
+
+    #[test]
+    fn test_replace_range() {
+        let mut string = Ume8String::from("aöӕ💻");
+        string.replace_range(1..5, &Ume8String::from("xธ"));
+
+        assert_eq!(string, Ume8String::from("axธ💻"));
+
+        string.replace_range(5.., &Ume8String::new());
+
+        assert_eq!(string, Ume8String::from("axธ"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replace_range_inside_char() {
+        let mut string = Ume8String::from("aö");
+        string.replace_range(..2, &Ume8String::new());
+    }
+
+    #[test]
+    fn test_add() {
+        let mut string = Ume8String::from("a") + &Ume8String::from("ö");
+        string += &Ume8String::from("💻");
+
+        assert_eq!(string, Ume8String::from("aö💻"));
+    }
+
+    #[test]
+    fn test_extend_and_collect() {
+        let ume8_string = Ume8String::from("ӕ");
+        let boxed: Box<Ume8Str> = Ume8String::from("ธ").into();
+
+        let mut string: Ume8String = vec!["a", "ö"].into_iter().collect();
+        string.extend(vec![String::from("b")]);
+        string.extend(vec![ume8_string.as_str()]);
+        string.extend(vec![boxed]);
+        string.extend(vec![Cow::Borrowed(ume8_string.as_str())]);
+
+        assert_eq!(string, Ume8String::from("aöbӕธӕ"));
+    }
+
+    #[test]
+    fn test_boxed_str() {
+        let boxed = Ume8String::from("aö💻").into_boxed_str();
+
+        assert_eq!(boxed.as_bytes(), Ume8String::from("aö💻").as_bytes());
+        assert_eq!(Ume8String::from(boxed), Ume8String::from("aö💻"));
+    }
+
+    #[test]
+    fn test_ascii_conversion() {
+        let string = Ume8String::from("abc");
+        let ptr = string.as_bytes().as_ptr();
+
+        assert_eq!(string.as_ascii_str(), Some("abc"));
+        assert_eq!(Ume8Str::from_ascii("abc"), Some(string.as_str()));
+
+        let ascii_string = string.into_ascii_string().unwrap();
+
+        assert_eq!(ascii_string, "abc");
+        assert_eq!(ascii_string.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_ascii_conversion_non_ascii() {
+        let string = Ume8String::from("aö");
+
+        assert_eq!(string.as_ascii_str(), None);
+        assert_eq!(Ume8Str::from_ascii("aö"), None);
+        assert_eq!(string.clone().into_ascii_string(), Err(string));
+    }
+
+    #[test]
+    fn test_utf8_string_conversion_in_place() {
+        let string = String::from("aöӕธ💻");
+        let ptr = string.as_ptr();
+        let offsets = string.char_indices().collect::<Vec<_>>();
+
+        let ume8_string = Ume8String::from_utf8_string(string);
+
+        assert_eq!(ume8_string, Ume8String::from("aöӕธ💻"));
+        assert_eq!(ume8_string.as_bytes().as_ptr(), ptr);
+        for (offset, ch) in offsets {
+            assert_eq!(ume8_string[offset..].chars().next(), Some(ch));
+        }
+
+        let string = ume8_string.into_utf8_string();
+
+        assert_eq!(string, "aöӕธ💻");
+        assert_eq!(string.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let string = Ume8String::from("aö💻");
+
+        assert_eq!(
+            Ume8String::from_bytes(string.as_bytes().to_vec()),
+            Ok(string.clone())
+        );
+        assert_eq!(Ume8Str::from_bytes(string.as_bytes()), Ok(string.as_str()));
+
+        let error = Ume8String::from_bytes(string.as_bytes()[..2].to_vec()).unwrap_err();
+
+        assert_eq!(error.ume8_error().valid_up_to(), 1);
+        assert_eq!(error.ume8_error().error_len(), None);
+        assert_eq!(error.into_bytes(), string.as_bytes()[..2].to_vec());
+    }
+
+    #[test]
+    fn test_chars() {
+        let string = Ume8String::from("aöӕธ💻");
+        let mut chars = string.chars();
+
+        assert_eq!(chars.next(), Some('a'));
+        assert_eq!(chars.next_back(), Some('💻'));
+        assert_eq!(chars.as_str(), &string[1..8]);
+        assert_eq!(chars.clone().count(), 3);
+        assert_eq!(chars.clone().last(), Some('ธ'));
+        assert_eq!(chars.collect::<String>(), "öӕธ");
+    }
+
+    #[test]
+    fn test_chars_nth() {
+        let string = Ume8String::from("aöӕธ💻");
+        let mut chars = string.chars();
+
+        assert_eq!(chars.nth(1), Some('ö'));
+        assert_eq!(chars.nth_back(1), Some('ธ'));
+        assert_eq!(chars.as_str(), &string[3..5]);
+        assert_eq!(chars.advance_by(2), Err(NonZeroUsize::new(1).unwrap()));
+        assert_eq!(string.chars().advance_back_by(5), Ok(()));
+    }
+
+    #[test]
+    fn test_from_str_allocates_exactly() {
+        let string = Ume8String::from("aöӕธ💻");
+
+        assert_eq!(string.capacity(), string.len());
+    }
+
+    #[test]
+    fn test_format_macro() {
+        let string = crate::ume8_format!("{}-{:>4}-{}", 1, 'ö', Ume8String::from("💻"));
+
+        assert_eq!(string, Ume8String::from("1-   ö-💻"));
+    }
+}
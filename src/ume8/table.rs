@@ -0,0 +1,165 @@
+use crate::ume8::{
+    MASK_SEQ, MASK_SEQ_CONT_DATA, MASK_SEQ_END, MASK_SEQ_START, MASK_SEQ_START_DATA,
+};
+
+/// Role of a byte in UME-8.
+///
+/// Every byte value has exactly one kind, so there is no separate kind for invalid bytes.
+/// Whether a byte is valid depends on its position.
+// The discriminants index the DFA below.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ByteKind {
+    /// `0xxxxxxx`, a complete single-byte sequence.
+    Singleton = 0,
+    /// `11xxxxxx`, the first byte of a multi-byte sequence.
+    Start = 1,
+    /// `100xxxxx`, a byte inside a multi-byte sequence.
+    Continuation = 2,
+    /// `101xxxxx`, the last byte of a multi-byte sequence.
+    End = 3,
+}
+
+pub const fn classify(byte: u8) -> ByteKind {
+    if byte & MASK_SEQ == 0 {
+        ByteKind::Singleton
+    } else if byte & MASK_SEQ_START != 0 {
+        ByteKind::Start
+    } else if byte & MASK_SEQ_END != 0 {
+        ByteKind::End
+    } else {
+        ByteKind::Continuation
+    }
+}
+
+pub(crate) static BYTE_KINDS: [ByteKind; 256] = {
+    let mut kinds = [ByteKind::Singleton; 256];
+    let mut byte = 0;
+
+    while byte < 256 {
+        kinds[byte] = classify(byte as u8);
+        byte += 1;
+    }
+
+    kinds
+};
+
+pub(crate) static DATA_MASKS: [u8; 4] = [
+    0b0111_1111,
+    MASK_SEQ_START_DATA,
+    MASK_SEQ_CONT_DATA,
+    MASK_SEQ_CONT_DATA,
+];
+
+// DFA states, named after the number of bytes of the current sequence seen so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum State {
+    Accept = 0,
+    Seq1 = 1,
+    Seq2 = 2,
+    Seq3 = 3,
+    Reject = 4,
+}
+
+// Indexed by state and byte kind. Sequences are limited to 4 bytes.
+pub(crate) static TRANSITIONS: [[State; 4]; 5] = {
+    use State::*;
+
+    [
+        // Singleton, Start, Continuation, End
+        [Accept, Seq1, Reject, Reject],   // Accept
+        [Reject, Reject, Seq2, Accept],   // Seq1
+        [Reject, Reject, Seq3, Accept],   // Seq2
+        [Reject, Reject, Reject, Accept], // Seq3
+        [Reject, Reject, Reject, Reject], // Reject
+    ]
+};
+
+#[inline(always)]
+pub(crate) fn kind_of(byte: u8) -> ByteKind {
+    BYTE_KINDS[byte as usize]
+}
+
+#[inline(always)]
+pub(crate) fn transition(state: State, kind: ByteKind) -> State {
+    TRANSITIONS[state as usize][kind as usize & 3]
+}
+
+#[inline(always)]
+pub(crate) fn accumulate(data: u32, byte: u8, kind: ByteKind) -> u32 {
+    (data << 5) | (byte & DATA_MASKS[kind as usize & 3]) as u32
+}
+
+// Decodes the sequence at the start of `bytes` without checking whether it is canonical
+// or a Unicode scalar value, and returns its value and length.
+#[inline]
+pub(crate) fn decode_sequence(bytes: &[u8]) -> Option<(u32, usize)> {
+    let mut state = State::Accept;
+    let mut data = 0;
+
+    for (index, &byte) in bytes.iter().enumerate() {
+        let kind = kind_of(byte);
+
+        state = transition(state, kind);
+        data = accumulate(data, byte, kind);
+
+        match state {
+            State::Accept => return Some((data, index + 1)),
+            State::Reject => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_kinds() {
+        for byte in 0..=255u8 {
+            let expected = match byte {
+                0x00..=0x7F => ByteKind::Singleton,
+                0x80..=0x9F => ByteKind::Continuation,
+                0xA0..=0xBF => ByteKind::End,
+                0xC0..=0xFF => ByteKind::Start,
+            };
+
+            assert_eq!(kind_of(byte), expected);
+        }
+    }
+
+    #[test]
+    fn test_transitions() {
+        use ByteKind::*;
+
+        let accepts = |kinds: &[ByteKind]| {
+            kinds.iter().try_fold(State::Accept, |state, &kind| {
+                match transition(state, kind) {
+                    State::Reject => None,
+                    next => Some(next),
+                }
+            }) == Some(State::Accept)
+        };
+
+        assert!(accepts(&[Singleton]));
+        assert!(accepts(&[Start, End]));
+        assert!(accepts(&[Start, Continuation, End]));
+        assert!(accepts(&[Start, Continuation, Continuation, End]));
+        assert!(!accepts(&[
+            Start,
+            Continuation,
+            Continuation,
+            Continuation,
+            End
+        ]));
+        assert!(!accepts(&[Start]));
+        assert!(!accepts(&[End]));
+        assert!(!accepts(&[Continuation]));
+        assert!(!accepts(&[Start, Singleton]));
+        assert!(!accepts(&[Start, Start, End]));
+    }
+}
@@ -0,0 +1,450 @@
+use crate::ume8::simd;
+use crate::ume8::util::len_ume8;
+use crate::ume8::{
+    MASK_SEQ, MASK_SEQ_CONT_DATA, MASK_SEQ_END, MASK_SEQ_START, MASK_SEQ_START_DATA,
+};
+
+// `len_ume8` is also the UTF-8 length of every scalar value, which allows transcoding in
+// place without moving any sequence.
+
+// Bytes of the UME-8 sequence of `data`, which is `len` bytes long, packed into a
+// little-endian word so that they can be written with a single store.
+#[inline(always)]
+fn ume8_sequence_word(data: u32, len: usize) -> u32 {
+    if len == 1 {
+        return data;
+    }
+
+    let last = len - 1;
+    let mut word =
+        ((data >> (5 * last)) & MASK_SEQ_START_DATA as u32) | (MASK_SEQ | MASK_SEQ_START) as u32;
+    for index in 1..len {
+        let byte = ((data >> (5 * (last - index))) & MASK_SEQ_CONT_DATA as u32) | MASK_SEQ as u32;
+        word |= byte << (8 * index);
+    }
+
+    word | (MASK_SEQ_END as u32) << (8 * last)
+}
+
+// Bytes of the UTF-8 sequence of `data`, which is `len` bytes long, packed like
+// `ume8_sequence_word`.
+#[inline(always)]
+fn utf8_sequence_word(data: u32, len: usize) -> u32 {
+    if len == 1 {
+        return data;
+    }
+
+    let last = len - 1;
+    let mut word = (data >> (6 * last)) | ((0b1111_0000 << (3 - last)) & 0xFF);
+    for index in 1..len {
+        let byte = ((data >> (6 * (last - index))) & 0b0011_1111) | 0b1000_0000;
+        word |= byte << (8 * index);
+    }
+
+    word
+}
+
+#[inline(always)]
+pub(crate) fn write_ume8_sequence(data: u32, bytes: &mut [u8]) {
+    let word = ume8_sequence_word(data, bytes.len());
+    bytes.copy_from_slice(&word.to_le_bytes()[..bytes.len()]);
+}
+
+#[inline(always)]
+fn write_utf8_sequence(data: u32, bytes: &mut [u8]) {
+    let word = utf8_sequence_word(data, bytes.len());
+    bytes.copy_from_slice(&word.to_le_bytes()[..bytes.len()]);
+}
+
+#[inline]
+fn is_valid_char(data: u32) -> bool {
+    data <= 0x10FFFF && !(0xD800..=0xDFFF).contains(&data)
+}
+
+// Decodes the UTF-8 sequence starting at `index` and returns its value and length.
+//
+// Panics if the sequence is not valid UTF-8.
+#[inline(always)]
+fn read_utf8_sequence(bytes: &[u8], index: usize) -> (u32, usize) {
+    let first_byte = bytes[index];
+    let len = first_byte.leading_ones() as usize;
+
+    assert!(
+        (2..=4).contains(&len) && index + len <= bytes.len(),
+        "invalid UTF-8 sequence at byte {}",
+        index
+    );
+
+    let mut data = (first_byte & (0b0111_1111 >> len)) as u32;
+    for &byte in &bytes[index + 1..index + len] {
+        assert!(
+            byte & 0b1100_0000 == 0b1000_0000,
+            "invalid UTF-8 sequence at byte {}",
+            index
+        );
+
+        data = (data << 6) | (byte & 0b0011_1111) as u32;
+    }
+
+    assert!(
+        len_ume8(data) == Some(len) && is_valid_char(data),
+        "invalid UTF-8 sequence at byte {}",
+        index
+    );
+
+    (data, len)
+}
+
+// Decodes the multi-byte UME-8 sequence starting at `index` and returns its value and
+// length. Overlong sequences are decoded as well.
+//
+// Panics if the sequence is not valid UME-8.
+#[inline(always)]
+fn read_ume8_sequence(bytes: &[u8], index: usize) -> (u32, usize) {
+    let first_byte = bytes[index];
+
+    assert!(
+        first_byte & MASK_SEQ_START != 0,
+        "invalid UME-8 sequence at byte {}",
+        index
+    );
+
+    let mut data = (first_byte & MASK_SEQ_START_DATA) as u32;
+    let mut len = 1;
+
+    loop {
+        let next_byte = bytes.get(index + len).copied().unwrap_or(0);
+
+        assert!(
+            next_byte & MASK_SEQ != 0 && next_byte & MASK_SEQ_START == 0 && len < 4,
+            "invalid UME-8 sequence at byte {}",
+            index
+        );
+
+        data = (data << 5) | (next_byte & MASK_SEQ_CONT_DATA) as u32;
+        len += 1;
+
+        if next_byte & MASK_SEQ_END != 0 {
+            break;
+        }
+    }
+
+    assert!(
+        is_valid_char(data),
+        "invalid UME-8 sequence at byte {}",
+        index
+    );
+
+    (data, len)
+}
+
+/// Rewrites UTF-8 as UME-8.
+///
+/// # Panics
+///
+/// Panics if `bytes` is not valid UTF-8.
+pub(crate) fn utf8_to_ume8_in_place(bytes: &mut [u8]) {
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] < 0x80 {
+            index += simd::ascii_len(&bytes[index..]);
+            continue;
+        }
+
+        let (data, len) = read_utf8_sequence(bytes, index);
+        write_ume8_sequence(data, &mut bytes[index..index + len]);
+        index += len;
+    }
+}
+
+/// Rewrites UME-8 as UTF-8 and returns the length of the UTF-8 data.
+///
+/// The result is shorter than the input only if the input contains overlong sequences,
+/// which are decoded even though they are not valid UME-8.
+///
+/// # Panics
+///
+/// Panics if `bytes` is not valid UME-8.
+pub(crate) fn ume8_to_utf8_in_place(bytes: &mut [u8]) -> usize {
+    let mut read_index = 0;
+    let mut write_index = 0;
+
+    while read_index < bytes.len() {
+        if bytes[read_index] < 0x80 {
+            let ascii_len = simd::ascii_len(&bytes[read_index..]);
+            if read_index != write_index {
+                bytes.copy_within(read_index..read_index + ascii_len, write_index);
+            }
+            read_index += ascii_len;
+            write_index += ascii_len;
+            continue;
+        }
+
+        let (data, len) = read_ume8_sequence(bytes, read_index);
+        let utf8_len = len_ume8(data).unwrap();
+        write_utf8_sequence(data, &mut bytes[write_index..write_index + utf8_len]);
+        read_index += len;
+        write_index += utf8_len;
+    }
+
+    write_index
+}
+
+// Destination of a single-pass transcoding, which receives runs of ASCII and single
+// sequences in order.
+trait Output {
+    fn push_ascii(&mut self, bytes: &[u8]);
+
+    // Pushes the first `len` bytes of a word built by `ume8_sequence_word` or
+    // `utf8_sequence_word`.
+    fn push_sequence(&mut self, word: u32, len: usize);
+}
+
+struct SliceOutput<'a> {
+    bytes: &'a mut [u8],
+    len: usize,
+}
+
+impl Output for SliceOutput<'_> {
+    #[inline]
+    fn push_ascii(&mut self, bytes: &[u8]) {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    #[inline]
+    fn push_sequence(&mut self, word: u32, len: usize) {
+        // storing the whole word is cheaper than copying `len` bytes, and the bytes past
+        // `len` are overwritten by whatever comes next
+        match self.bytes.get_mut(self.len..self.len + 4) {
+            Some(dst) => dst.copy_from_slice(&word.to_le_bytes()),
+            None => {
+                self.bytes[self.len..self.len + len].copy_from_slice(&word.to_le_bytes()[..len])
+            }
+        }
+        self.len += len;
+    }
+}
+
+impl Output for Vec<u8> {
+    #[inline]
+    fn push_ascii(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    #[inline]
+    fn push_sequence(&mut self, word: u32, len: usize) {
+        match self.spare_capacity_mut().get_mut(..4) {
+            Some(dst) => {
+                for (dst, byte) in dst.iter_mut().zip(word.to_le_bytes()) {
+                    dst.write(byte);
+                }
+                // SAFETY: the first `len` bytes of the spare capacity were just written
+                unsafe { self.set_len(self.len() + len) };
+            }
+            None => self.extend_from_slice(&word.to_le_bytes()[..len]),
+        }
+    }
+}
+
+// Transcodes UTF-8 to UME-8 in a single pass.
+fn utf8_to_ume8_into<O: Output>(src: &[u8], dst: &mut O) {
+    let mut index = 0;
+
+    while index < src.len() {
+        if src[index] < 0x80 {
+            let ascii_len = simd::ascii_len(&src[index..]);
+            dst.push_ascii(&src[index..index + ascii_len]);
+            index += ascii_len;
+            continue;
+        }
+
+        let (data, len) = read_utf8_sequence(src, index);
+        dst.push_sequence(ume8_sequence_word(data, len), len);
+        index += len;
+    }
+}
+
+// Transcodes UME-8 to UTF-8 in a single pass.
+fn ume8_to_utf8_into<O: Output>(src: &[u8], dst: &mut O) {
+    let mut index = 0;
+
+    while index < src.len() {
+        if src[index] < 0x80 {
+            let ascii_len = simd::ascii_len(&src[index..]);
+            dst.push_ascii(&src[index..index + ascii_len]);
+            index += ascii_len;
+            continue;
+        }
+
+        let (data, len) = read_ume8_sequence(src, index);
+        let utf8_len = len_ume8(data).unwrap();
+        dst.push_sequence(utf8_sequence_word(data, utf8_len), utf8_len);
+        index += len;
+    }
+}
+
+/// Transcodes UTF-8 in `src` to UME-8 in `dst` and returns the number of bytes written,
+/// which always equals `src.len()`.
+///
+/// # Panics
+///
+/// Panics if `src` is not valid UTF-8 or if `dst` is shorter than `src`.
+pub fn encode_utf8_to_ume8(src: &[u8], dst: &mut [u8]) -> usize {
+    let mut output = SliceOutput {
+        bytes: &mut dst[..src.len()],
+        len: 0,
+    };
+    utf8_to_ume8_into(src, &mut output);
+    output.len
+}
+
+/// Transcodes UME-8 in `src` to UTF-8 in `dst` and returns the number of bytes written.
+///
+/// Fewer than `src.len()` bytes are written only if `src` contains overlong sequences,
+/// which are decoded even though they are not valid UME-8.
+///
+/// # Panics
+///
+/// Panics if `src` is not valid UME-8 or if `dst` is shorter than `src`.
+pub fn decode_ume8_to_utf8(src: &[u8], dst: &mut [u8]) -> usize {
+    let mut output = SliceOutput {
+        bytes: &mut dst[..src.len()],
+        len: 0,
+    };
+    ume8_to_utf8_into(src, &mut output);
+    output.len
+}
+
+// Transcodes UTF-8 to a new buffer of UME-8.
+//
+// Panics if `src` is not valid UTF-8.
+pub(crate) fn utf8_to_ume8_vec(src: &[u8]) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(src.len());
+    utf8_to_ume8_into(src, &mut dst);
+    dst
+}
+
+// Transcodes UME-8 to a new buffer of UTF-8.
+//
+// Panics if `src` is not valid UME-8.
+pub(crate) fn ume8_to_utf8_vec(src: &[u8]) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(src.len());
+    ume8_to_utf8_into(src, &mut dst);
+    dst
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ume8::EncodeUnchecked;
+
+    fn utf8_data() -> Vec<(String, Vec<u8>)> {
+        super::super::tests::data()
+            .into_iter()
+            .map(|(decoded, encoded)| {
+                let string = decoded
+                    .into_iter()
+                    .map(|data| char::from_u32(data).unwrap())
+                    .collect::<String>();
+
+                (string, encoded)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_utf8_to_ume8_in_place() {
+        for (string, encoded) in utf8_data() {
+            let mut bytes = string.into_bytes();
+            utf8_to_ume8_in_place(&mut bytes);
+
+            assert_eq!(bytes, encoded);
+        }
+    }
+
+    #[test]
+    fn test_ume8_to_utf8_in_place() {
+        for (string, encoded) in utf8_data() {
+            let mut bytes = encoded.clone();
+            let len = ume8_to_utf8_in_place(&mut bytes);
+
+            assert_eq!(&bytes[..len], string.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_ume8_to_utf8_in_place_overlong() {
+        let mut bytes = vec![
+            b'a', 0b11000000, 0b10100001, 0b11000001, 0b10000110, 0b10110101,
+        ];
+        let len = ume8_to_utf8_in_place(&mut bytes);
+
+        assert_eq!(&bytes[..len], "a\u{1}ӕ".as_bytes());
+    }
+
+    #[test]
+    fn test_bulk_transcode() {
+        for (string, encoded) in utf8_data() {
+            let mut ume8 = vec![0; string.len() + 3];
+            let ume8_len = encode_utf8_to_ume8(string.as_bytes(), &mut ume8);
+
+            assert_eq!(&ume8[..ume8_len], encoded);
+
+            let mut utf8 = vec![0; encoded.len()];
+            let utf8_len = decode_ume8_to_utf8(&encoded, &mut utf8);
+
+            assert_eq!(&utf8[..utf8_len], string.as_bytes());
+
+            assert_eq!(utf8_to_ume8_vec(string.as_bytes()), encoded);
+            assert_eq!(ume8_to_utf8_vec(&encoded), string.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_bulk_transcode_all_chars() {
+        let string = (0..=0x10FFFF)
+            .filter_map(char::from_u32)
+            .collect::<String>();
+        let expected = EncodeUnchecked::new(string.chars().map(|ch| ch as u32)).collect::<Vec<_>>();
+
+        let mut ume8 = vec![0; string.len()];
+        encode_utf8_to_ume8(string.as_bytes(), &mut ume8);
+
+        assert_eq!(ume8, expected);
+
+        let mut utf8 = vec![0; ume8.len()];
+        decode_ume8_to_utf8(&ume8, &mut utf8);
+
+        assert_eq!(utf8, string.as_bytes());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encode_invalid_utf8() {
+        let mut dst = [0; 3];
+        encode_utf8_to_ume8(&[b'a', 0xED, 0xA0, 0x80][..3], &mut dst);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encode_short_dst() {
+        let mut dst = [0; 1];
+        encode_utf8_to_ume8("ö".as_bytes(), &mut dst);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ume8_to_utf8_in_place_invalid() {
+        let mut bytes = vec![b'a', 0b10100001];
+        ume8_to_utf8_in_place(&mut bytes);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ume8_to_utf8_in_place_truncated() {
+        let mut bytes = vec![b'a', 0b11000000, 0b10000001];
+        ume8_to_utf8_in_place(&mut bytes);
+    }
+}
@@ -1,10 +1,33 @@
 #![allow(dead_code)]
 
-use crate::ume8::{MASK_SEQ, MASK_SEQ_START};
+use crate::ume8::{MASK_SEQ, MASK_SEQ_END, MASK_SEQ_START};
+
+// Length of the longest sequence, so a sequence starts at most `MAX_SEQUENCE_LEN - 1`
+// bytes before any of its bytes.
+pub const MAX_SEQUENCE_LEN: usize = 4;
+
+/// Number of bytes needed to encode `data`, or `None` if it has more than 21 bits. UME-8
+/// needs as many bytes per scalar value as UTF-8:
+///
+/// | Scalar value        | Bytes |
+/// | ------------------- | ----- |
+/// | U+0000 - U+007F     | 1     |
+/// | U+0080 - U+07FF     | 2     |
+/// | U+0800 - U+FFFF     | 3     |
+/// | U+10000 - U+10FFFF  | 4     |
+#[inline]
+pub const fn len_ume8(data: u32) -> Option<usize> {
+    match data {
+        0..=0x7F => Some(1),
+        0x80..=0x7FF => Some(2),
+        0x800..=0xFFFF => Some(3),
+        0x10000..=0x1FFFFF => Some(4),
+        _ => None,
+    }
+}
 
 pub fn count_sequences_unchecked<I: Iterator<Item = u8>>(iter: I) -> usize {
-    iter.filter(|byte| byte & MASK_SEQ == 0 || byte & MASK_SEQ_START != 0)
-        .count()
+    iter.filter(is_char_start).count()
 }
 
 #[inline]
@@ -17,6 +40,17 @@ pub fn is_singleton(byte: &u8) -> bool {
     byte & MASK_SEQ == 0
 }
 
+#[inline]
+pub fn is_char_start(byte: &u8) -> bool {
+    byte & MASK_SEQ == 0 || byte & MASK_SEQ_START != 0
+}
+
+#[inline]
+pub fn is_sequence_end(byte: &u8) -> bool {
+    byte & MASK_SEQ == 0
+        || byte & (MASK_SEQ | MASK_SEQ_START | MASK_SEQ_END) == MASK_SEQ | MASK_SEQ_END
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,6 +64,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_len_ume8() {
+        for (decoded, encoded) in super::super::tests::data() {
+            let len = decoded
+                .iter()
+                .map(|&data| len_ume8(data).unwrap())
+                .sum::<usize>();
+
+            assert_eq!(len, encoded.len());
+        }
+
+        assert_eq!(len_ume8(0x1FFFFF), Some(4));
+        assert_eq!(len_ume8(0x200000), None);
+    }
+
     #[test]
     fn test_is_sequence_part() {
         assert_eq!(is_sequence_part(&0b10000000), true);
@@ -45,4 +94,24 @@ mod tests {
         assert_eq!(is_singleton(&0b00000000), true);
         assert_eq!(is_singleton(&0b01111111), true);
     }
+
+    #[test]
+    fn test_is_char_start() {
+        assert!(is_char_start(&0b00000000));
+        assert!(is_char_start(&0b01111111));
+        assert!(is_char_start(&0b11000000));
+        assert!(is_char_start(&0b11111111));
+        assert!(!is_char_start(&0b10000000));
+        assert!(!is_char_start(&0b10111111));
+    }
+
+    #[test]
+    fn test_is_sequence_end() {
+        assert!(is_sequence_end(&0b00000000));
+        assert!(is_sequence_end(&0b01111111));
+        assert!(is_sequence_end(&0b10100000));
+        assert!(is_sequence_end(&0b10111111));
+        assert!(!is_sequence_end(&0b10000000));
+        assert!(!is_sequence_end(&0b11100000));
+    }
 }
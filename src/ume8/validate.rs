@@ -0,0 +1,257 @@
+use crate::ume8::simd;
+use crate::ume8::table::{self, ByteKind, State};
+use crate::ume8::util::{len_ume8, MAX_SEQUENCE_LEN};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ume8Error {
+    valid_up_to: usize,
+    error_len: Option<u8>,
+}
+
+impl Ume8Error {
+    pub(crate) fn new(valid_up_to: usize, error_len: Option<usize>) -> Self {
+        Self {
+            valid_up_to,
+            error_len: error_len.map(|len| len as u8),
+        }
+    }
+
+    /// Index up to which the input contains valid UME-8.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /// Length of the invalid byte sequence, or `None` if the input ends in the middle of
+    /// a sequence.
+    pub fn error_len(&self) -> Option<usize> {
+        self.error_len.map(|len| len as usize)
+    }
+}
+
+impl Display for Ume8Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.error_len {
+            Some(error_len) => write!(
+                f,
+                "invalid UME-8 sequence of {} bytes from index {}",
+                error_len, self.valid_up_to
+            ),
+            None => write!(
+                f,
+                "incomplete UME-8 byte sequence from index {}",
+                self.valid_up_to
+            ),
+        }
+    }
+}
+
+impl Error for Ume8Error {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FromUme8Error {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) error: Ume8Error,
+}
+
+impl FromUme8Error {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn ume8_error(&self) -> Ume8Error {
+        self.error
+    }
+}
+
+impl Display for FromUme8Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for FromUme8Error {}
+
+pub fn validate(bytes: &[u8]) -> Result<(), Ume8Error> {
+    validate_with(bytes, simd::ascii_len, simd::valid_sequences_end)
+}
+
+#[cfg(test)]
+pub(crate) fn validate_scalar(bytes: &[u8]) -> Result<(), Ume8Error> {
+    validate_with(bytes, simd::ascii_len_scalar, |_, start| start)
+}
+
+#[inline(always)]
+fn validate_with(
+    bytes: &[u8],
+    ascii_len: fn(&[u8]) -> usize,
+    valid_end: fn(&[u8], usize) -> usize,
+) -> Result<(), Ume8Error> {
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] < 0x80 {
+            index += ascii_len(&bytes[index..]);
+            continue;
+        }
+
+        // The vectorized check needs the three bytes before `index`, and stops before
+        // any invalid sequence, which is then reported by `validate_sequence`.
+        if index >= MAX_SEQUENCE_LEN - 1 {
+            let end = valid_end(bytes, index);
+            if end > index {
+                index = end;
+                continue;
+            }
+        }
+
+        let len = validate_sequence(&bytes[index..])
+            .map_err(|error_len| Ume8Error::new(index, error_len.map(usize::from)))?;
+
+        index += len;
+    }
+
+    Ok(())
+}
+
+// Validates the sequence at the start of `bytes` and returns its length.
+#[inline]
+fn validate_sequence(bytes: &[u8]) -> Result<usize, Option<u8>> {
+    let mut state = State::Accept;
+    let mut data = 0;
+
+    for (index, &byte) in bytes.iter().enumerate() {
+        let kind = table::kind_of(byte);
+
+        state = table::transition(state, kind);
+        data = table::accumulate(data, byte, kind);
+
+        match state {
+            // only the shortest encoding of a char is valid, so that every string has a
+            // single representation
+            State::Accept
+                if char::from_u32(data).is_some() && len_ume8(data) == Some(index + 1) =>
+            {
+                return Ok(index + 1)
+            }
+            State::Accept => return Err(Some(index as u8 + 1)),
+            // the invalid sequence ends before a singleton or start byte
+            State::Reject if matches!(kind, ByteKind::Singleton | ByteKind::Start) => {
+                return Err(Some(index as u8))
+            }
+            State::Reject => return Err(Some(index as u8 + 1)),
+            _ => {}
+        }
+    }
+
+    Err(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(valid_up_to: usize, error_len: Option<u8>) -> Result<(), Ume8Error> {
+        Err(Ume8Error {
+            valid_up_to,
+            error_len,
+        })
+    }
+
+    #[test]
+    fn test_validate_valid() {
+        for (_, encoded) in super::super::tests::data() {
+            assert_eq!(validate(&encoded), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        // lone continuation and end bytes
+        assert_eq!(validate(&[b'a', 0b10000001]), error(1, Some(1)));
+        assert_eq!(validate(&[0b10100001]), error(0, Some(1)));
+        // sequence interrupted by a singleton or start byte
+        assert_eq!(validate(&[0b11000001, b'a']), error(0, Some(1)));
+        assert_eq!(
+            validate(&[b'a', 0b11000001, 0b10000001, 0b11000001, 0b10100001]),
+            error(1, Some(2))
+        );
+        // incomplete sequence
+        assert_eq!(validate(&[b'a', 0b11000001]), error(1, None));
+        assert_eq!(validate(&[0b11000001, 0b10000001]), error(0, None));
+        // more than 4 bytes
+        assert_eq!(
+            validate(&[0b11000000, 0b10000000, 0b10000000, 0b10000000, 0b10100000]),
+            error(0, Some(4))
+        );
+        // above U+10FFFF
+        assert_eq!(
+            validate(&[0b11100010, 0b10000000, 0b10000000, 0b10100000]),
+            error(0, Some(4))
+        );
+        // surrogate
+        assert_eq!(
+            validate(&[0b11110110, 0b10000000, 0b10100000]),
+            error(0, Some(3))
+        );
+    }
+
+    #[test]
+    fn test_validate_overlong() {
+        // U+007F / U+0080
+        assert_eq!(validate(&[0x7F]), Ok(()));
+        assert_eq!(validate(&[0b11000011, 0b10111111]), error(0, Some(2)));
+        assert_eq!(validate(&[0b11000100, 0b10100000]), Ok(()));
+        assert_eq!(
+            validate(&[0b11000000, 0b10000100, 0b10100000]),
+            error(0, Some(3))
+        );
+        // U+07FF / U+0800
+        assert_eq!(validate(&[0b11111111, 0b10111111]), Ok(()));
+        assert_eq!(
+            validate(&[0b11000001, 0b10011111, 0b10111111]),
+            error(0, Some(3))
+        );
+        assert_eq!(validate(&[0b11000010, 0b10000000, 0b10100000]), Ok(()));
+        assert_eq!(
+            validate(&[0b11000000, 0b10000010, 0b10000000, 0b10100000]),
+            error(0, Some(4))
+        );
+        // U+FFFF / U+10000
+        assert_eq!(validate(&[0b11111111, 0b10011111, 0b10111111]), Ok(()));
+        assert_eq!(
+            validate(&[0b11000001, 0b10011111, 0b10011111, 0b10111111]),
+            error(0, Some(4))
+        );
+        assert_eq!(
+            validate(&[0b11000010, 0b10000000, 0b10000000, 0b10100000]),
+            Ok(())
+        );
+        // U+0000 as two bytes
+        assert_eq!(validate(&[b'a', 0b11000000, 0b10100000]), error(1, Some(2)));
+    }
+
+    #[test]
+    fn test_validate_matches_scalar() {
+        for bytes in super::super::simd::tests::samples() {
+            assert_eq!(validate(&bytes), validate_scalar(&bytes));
+        }
+    }
+
+    #[test]
+    fn test_validate_matches_decoder() {
+        for bytes in super::super::simd::tests::samples() {
+            if validate(&bytes).is_ok() {
+                let decoded =
+                    crate::ume8::DecodeUnchecked::new(bytes.iter().cloned()).collect::<Vec<u32>>();
+
+                assert!(decoded.iter().all(|&data| char::from_u32(data).is_some()));
+            }
+        }
+    }
+}
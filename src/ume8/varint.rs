@@ -0,0 +1,258 @@
+//! Variable-length integers using the UME sequence layout.
+//!
+//! Values are encoded like [`wide`] sequences, so small values take a
+//! single byte and every value ends with a singleton or end byte. Signed values are
+//! zigzag encoded first, so that small negative values stay short.
+
+use crate::ume8::wide::{self, Codec, WideError};
+use crate::ume8::{classify, ByteKind};
+use std::io::{self, Read, Write};
+
+/// Length of the longest encoded `u64`.
+pub const MAX_LEN: usize = Codec::<u64>::MAX_LEN;
+
+pub fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+pub fn encoded_len(value: u64) -> usize {
+    wide::encoded_len(value)
+}
+
+/// Appends the encoded value to `dst` and returns the number of bytes written.
+pub fn write_u64(value: u64, dst: &mut Vec<u8>) -> usize {
+    Codec::<u64>::new().encode(value, dst).unwrap()
+}
+
+/// Appends the zigzag encoded value to `dst` and returns the number of bytes written.
+pub fn write_i64(value: i64, dst: &mut Vec<u8>) -> usize {
+    write_u64(zigzag_encode(value), dst)
+}
+
+/// Decodes the value at the start of `bytes` and returns it along with its length.
+pub fn read_u64(bytes: &[u8]) -> Result<(u64, usize), WideError> {
+    Codec::<u64>::new().decode(bytes)
+}
+
+/// Decodes the zigzag encoded value at the start of `bytes` and returns it along with its
+/// length.
+pub fn read_i64(bytes: &[u8]) -> Result<(i64, usize), WideError> {
+    read_u64(bytes).map(|(value, len)| (zigzag_decode(value), len))
+}
+
+pub fn encode_u64s(values: &[u64]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(values.len());
+    for &value in values {
+        write_u64(value, &mut bytes);
+    }
+    bytes
+}
+
+pub fn encode_i64s(values: &[i64]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(values.len());
+    for &value in values {
+        write_i64(value, &mut bytes);
+    }
+    bytes
+}
+
+/// Decodes all values of `bytes`.
+pub fn decode_u64s(bytes: &[u8]) -> Result<Vec<u64>, WideError> {
+    Codec::<u64>::new().decoder(bytes.iter().copied()).collect()
+}
+
+/// Decodes all zigzag encoded values of `bytes`.
+pub fn decode_i64s(bytes: &[u8]) -> Result<Vec<i64>, WideError> {
+    Codec::<u64>::new()
+        .decoder(bytes.iter().copied())
+        .map(|value| value.map(zigzag_decode))
+        .collect()
+}
+
+/// Reads variable-length integers from a byte stream.
+pub trait ReadVarint: Read {
+    /// Reads a single value, consuming exactly the bytes of its sequence if it is valid.
+    ///
+    /// Invalid sequences result in [`io::ErrorKind::InvalidData`].
+    fn read_u64_varint(&mut self) -> io::Result<u64> {
+        let mut buffer = [0; MAX_LEN];
+        let mut len = 0;
+
+        loop {
+            let mut byte = [0];
+            self.read_exact(&mut byte)?;
+            let byte = byte[0];
+
+            if len == MAX_LEN {
+                return Err(invalid_data(WideError::SequenceTooLong));
+            }
+            buffer[len] = byte;
+            len += 1;
+
+            // anything but a continuation byte ends the sequence, decoding reports it if
+            // it is invalid
+            let continues = match classify(byte) {
+                ByteKind::Start => len == 1,
+                ByteKind::Continuation => len > 1,
+                ByteKind::Singleton | ByteKind::End => false,
+            };
+            if !continues {
+                break;
+            }
+        }
+
+        read_u64(&buffer[..len])
+            .map(|(value, _)| value)
+            .map_err(invalid_data)
+    }
+
+    fn read_i64_varint(&mut self) -> io::Result<i64> {
+        self.read_u64_varint().map(zigzag_decode)
+    }
+
+    /// Reads `n` values.
+    fn read_u64_varints(&mut self, n: usize) -> io::Result<Vec<u64>> {
+        (0..n).map(|_| self.read_u64_varint()).collect()
+    }
+
+    /// Reads `n` zigzag encoded values.
+    fn read_i64_varints(&mut self, n: usize) -> io::Result<Vec<i64>> {
+        (0..n).map(|_| self.read_i64_varint()).collect()
+    }
+}
+
+impl<R: Read + ?Sized> ReadVarint for R {}
+
+/// Writes variable-length integers to a byte stream.
+pub trait WriteVarint: Write {
+    /// Writes a single value and returns the number of bytes written.
+    fn write_u64_varint(&mut self, value: u64) -> io::Result<usize> {
+        let mut buffer = [0; MAX_LEN];
+        let len = Codec::<u64>::new().encode_into(value, &mut buffer).unwrap();
+        self.write_all(&buffer[..len])?;
+        Ok(len)
+    }
+
+    fn write_i64_varint(&mut self, value: i64) -> io::Result<usize> {
+        self.write_u64_varint(zigzag_encode(value))
+    }
+
+    /// Writes all values with a single write and returns the number of bytes written.
+    fn write_u64_varints(&mut self, values: &[u64]) -> io::Result<usize> {
+        let bytes = encode_u64s(values);
+        self.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Writes all zigzag encoded values with a single write and returns the number of
+    /// bytes written.
+    fn write_i64_varints(&mut self, values: &[i64]) -> io::Result<usize> {
+        let bytes = encode_i64s(values);
+        self.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+impl<W: Write + ?Sized> WriteVarint for W {}
+
+fn invalid_data(error: WideError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values_i64() -> Vec<i64> {
+        let mut values = vec![0, -1, i64::MIN, i64::MAX];
+        for bits in 1..63 {
+            values.extend([1 << bits, -(1 << bits), (1 << bits) - 1, -(1 << bits) - 1]);
+        }
+        values
+    }
+
+    #[test]
+    fn test_zigzag() {
+        assert_eq!(zigzag_encode(0), 0);
+        assert_eq!(zigzag_encode(-1), 1);
+        assert_eq!(zigzag_encode(1), 2);
+        assert_eq!(zigzag_encode(i64::MAX), u64::MAX - 1);
+        assert_eq!(zigzag_encode(i64::MIN), u64::MAX);
+
+        for value in values_i64() {
+            assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_all_widths() {
+        for value in super::super::wide::tests::values_u64() {
+            let mut bytes = Vec::new();
+            let len = write_u64(value, &mut bytes);
+
+            assert_eq!(len, encoded_len(value));
+            assert_eq!(read_u64(&bytes), Ok((value, len)));
+        }
+
+        for value in values_i64() {
+            let mut bytes = Vec::new();
+            let len = write_i64(value, &mut bytes);
+
+            assert_eq!(read_i64(&bytes), Ok((value, len)));
+        }
+
+        assert_eq!(encoded_len(127), 1);
+        assert_eq!(encoded_len(128), 2);
+        assert_eq!(encoded_len(u64::MAX), MAX_LEN);
+    }
+
+    #[test]
+    fn test_slices() {
+        let values = super::super::wide::tests::values_u64();
+        assert_eq!(decode_u64s(&encode_u64s(&values)), Ok(values));
+
+        let values = values_i64();
+        assert_eq!(decode_i64s(&encode_i64s(&values)), Ok(values));
+
+        assert_eq!(
+            decode_u64s(&[1, 0b11000001]),
+            Err(WideError::IncompleteSequence)
+        );
+    }
+
+    #[test]
+    fn test_io() {
+        let values = super::super::wide::tests::values_u64();
+        let mut bytes = Vec::new();
+        bytes.write_u64_varints(&values).unwrap();
+        bytes.write_i64_varint(-3).unwrap();
+        bytes.write_u64_varint(300).unwrap();
+
+        let mut reader = io::Cursor::new(bytes);
+        assert_eq!(reader.read_u64_varints(values.len()).unwrap(), values);
+        assert_eq!(reader.read_i64_varint().unwrap(), -3);
+        assert_eq!(reader.read_u64_varint().unwrap(), 300);
+
+        let error = reader.read_u64_varint().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_io_invalid() {
+        let invalid: [&[u8]; 3] = [&[0b10100001], &[0b11000001, 1], &[0b11000000, 0b10100001]];
+
+        for bytes in invalid {
+            let error = io::Cursor::new(bytes).read_u64_varint().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let mut too_long = vec![0b11000001];
+        too_long.extend([0b10000000; MAX_LEN]);
+        let error = io::Cursor::new(too_long).read_u64_varint().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,446 @@
+//! UME sequences of arbitrary length, for values wider than 21 bits.
+//!
+//! The bytes follow the same grammar as UME-8 text: a start byte with 6 bits of data,
+//! continuation bytes and an end byte with 5 bits each, or a single singleton byte with
+//! 7 bits. A 64 bit value needs up to 13 bytes, a 128 bit value up to 26 bytes.
+
+use crate::ume8::{
+    MASK_SEQ, MASK_SEQ_CONT_DATA, MASK_SEQ_END, MASK_SEQ_START, MASK_SEQ_START_DATA,
+};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+/// Unsigned integer types that can be encoded, up to `u128`.
+pub trait Value: Copy + Into<u128> + TryFrom<u128> {}
+
+impl<T: Copy + Into<u128> + TryFrom<u128>> Value for T {}
+
+// Longest sequence of all value types.
+const BUFFER_LEN: usize = len_for_bits(128);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WideError {
+    /// A value that begins with a continuation or end byte.
+    UnexpectedByte(u8),
+    /// A value whose sequence has no end byte before the next value or the end of the
+    /// input.
+    IncompleteSequence,
+    /// A sequence longer than the maximum length of the codec.
+    SequenceTooLong,
+    /// A sequence that is longer than needed for its value.
+    OverlongSequence,
+    /// A sequence whose value does not fit into the value type.
+    Overflow,
+}
+
+impl Display for WideError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WideError::UnexpectedByte(byte) => write!(f, "unexpected byte {:#04x}", byte),
+            WideError::IncompleteSequence => write!(f, "incomplete sequence"),
+            WideError::SequenceTooLong => write!(f, "sequence longer than the maximum length"),
+            WideError::OverlongSequence => write!(f, "overlong sequence"),
+            WideError::Overflow => write!(f, "value does not fit into the value type"),
+        }
+    }
+}
+
+impl Error for WideError {}
+
+/// Number of bytes of a sequence with `bits` bits of data.
+pub const fn len_for_bits(bits: u32) -> usize {
+    if bits <= 7 {
+        return 1;
+    }
+
+    1 + (bits as usize - 6).div_ceil(5)
+}
+
+/// Number of bytes needed to encode `value`.
+pub fn encoded_len<T: Value>(value: T) -> usize {
+    len_for_bits(u128::BITS - value.into().leading_zeros())
+}
+
+/// Encodes and decodes values of type `T` with a maximum sequence length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Codec<T> {
+    max_len: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T: Value> Codec<T> {
+    /// Length of the longest sequence needed for a value of type `T`.
+    pub const MAX_LEN: usize = len_for_bits(std::mem::size_of::<T>() as u32 * 8);
+
+    /// Creates a codec that accepts every value of type `T`.
+    pub fn new() -> Self {
+        Self {
+            max_len: Self::MAX_LEN,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a codec that rejects sequences longer than `max_len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_len` is 0 or greater than [`Codec::MAX_LEN`].
+    pub fn with_max_len(max_len: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_LEN).contains(&max_len),
+            "maximum sequence length must be between 1 and {}",
+            Self::MAX_LEN
+        );
+
+        Self {
+            max_len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Appends the encoded value to `dst` and returns the number of bytes written.
+    pub fn encode(&self, value: T, dst: &mut Vec<u8>) -> Result<usize, WideError> {
+        let mut buffer = [0; BUFFER_LEN];
+        let len = self.encode_into(value, &mut buffer)?;
+        dst.extend_from_slice(&buffer[..len]);
+        Ok(len)
+    }
+
+    /// Decodes the sequence at the start of `bytes` and returns its value and length.
+    pub fn decode(&self, bytes: &[u8]) -> Result<(T, usize), WideError> {
+        let mut iter = bytes.iter().copied();
+        let mut pending = None;
+
+        decode_next(&mut iter, &mut pending, self.max_len)
+            .unwrap_or(Err(WideError::IncompleteSequence))
+    }
+
+    pub fn encoder<I: IntoIterator<Item = T>>(&self, iter: I) -> Encode<I::IntoIter, T> {
+        Encode {
+            iter: iter.into_iter(),
+            codec: *self,
+            buffer: [0; BUFFER_LEN],
+            next_index: 0,
+            len: 0,
+        }
+    }
+
+    pub fn decoder<I: IntoIterator<Item = u8>>(&self, iter: I) -> Decode<I::IntoIter, T> {
+        Decode {
+            iter: iter.into_iter(),
+            max_len: self.max_len,
+            pending: None,
+            marker: PhantomData,
+        }
+    }
+
+    // Encodes the value at the start of `buffer`, which must be able to hold it.
+    pub(crate) fn encode_into(&self, value: T, buffer: &mut [u8]) -> Result<usize, WideError> {
+        let data = value.into();
+        let len = encoded_len(value);
+
+        if len > self.max_len {
+            return Err(WideError::SequenceTooLong);
+        }
+
+        if len == 1 {
+            buffer[0] = data as u8;
+            return Ok(1);
+        }
+
+        let last = len - 1;
+        buffer[0] =
+            (((data >> (5 * last)) as u8) & MASK_SEQ_START_DATA) | MASK_SEQ | MASK_SEQ_START;
+        for (index, byte) in buffer[1..len].iter_mut().enumerate() {
+            *byte = (((data >> (5 * (last - 1 - index))) as u8) & MASK_SEQ_CONT_DATA) | MASK_SEQ;
+        }
+        buffer[last] |= MASK_SEQ_END;
+
+        Ok(len)
+    }
+}
+
+impl<T: Value> Default for Codec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Decodes the next sequence, pushing back the first byte of the next sequence if the
+// current one is interrupted.
+fn decode_next<I, T>(
+    iter: &mut I,
+    pending: &mut Option<u8>,
+    max_len: usize,
+) -> Option<Result<(T, usize), WideError>>
+where
+    I: Iterator<Item = u8>,
+    T: Value,
+{
+    let first_byte = pending.take().or_else(|| iter.next())?;
+
+    if first_byte & MASK_SEQ == 0 {
+        return Some(to_value(first_byte as u128, 1));
+    }
+    if first_byte & MASK_SEQ_START == 0 {
+        return Some(Err(WideError::UnexpectedByte(first_byte)));
+    }
+
+    let mut data = (first_byte & MASK_SEQ_START_DATA) as u128;
+    let mut len = 1;
+
+    loop {
+        let byte = match iter.next() {
+            Some(byte) => byte,
+            None => return Some(Err(WideError::IncompleteSequence)),
+        };
+
+        if byte & MASK_SEQ == 0 || byte & MASK_SEQ_START != 0 {
+            *pending = Some(byte);
+            return Some(Err(WideError::IncompleteSequence));
+        }
+
+        len += 1;
+        if len > max_len {
+            return Some(Err(WideError::SequenceTooLong));
+        }
+        if data.leading_zeros() < 5 {
+            return Some(Err(WideError::Overflow));
+        }
+
+        data = (data << 5) | (byte & MASK_SEQ_CONT_DATA) as u128;
+
+        if byte & MASK_SEQ_END != 0 {
+            break;
+        }
+    }
+
+    if encoded_len(data) != len {
+        return Some(Err(WideError::OverlongSequence));
+    }
+
+    Some(to_value(data, len))
+}
+
+fn to_value<T: Value>(data: u128, len: usize) -> Result<(T, usize), WideError> {
+    T::try_from(data)
+        .map(|value| (value, len))
+        .map_err(|_| WideError::Overflow)
+}
+
+/// Encodes values, yielding an error for every value that needs more bytes than allowed.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Encode<I, T> {
+    iter: I,
+    codec: Codec<T>,
+    buffer: [u8; BUFFER_LEN],
+    next_index: u8,
+    len: u8,
+}
+
+impl<I, T> Iterator for Encode<I, T>
+where
+    I: Iterator<Item = T>,
+    T: Value,
+{
+    type Item = Result<u8, WideError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index < self.len {
+            self.next_index += 1;
+            return Some(Ok(self.buffer[self.next_index as usize - 1]));
+        }
+
+        let value = self.iter.next()?;
+        self.len = match self.codec.encode_into(value, &mut self.buffer) {
+            Ok(len) => len as u8,
+            Err(error) => return Some(Err(error)),
+        };
+        self.next_index = 1;
+
+        Some(Ok(self.buffer[0]))
+    }
+}
+
+impl<I, T> FusedIterator for Encode<I, T>
+where
+    I: FusedIterator<Item = T>,
+    T: Value,
+{
+}
+
+/// Decodes values, yielding an error for every invalid sequence.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Decode<I, T> {
+    iter: I,
+    max_len: usize,
+    // byte that cut the previous value short, decoded as the start of the next value
+    pending: Option<u8>,
+    marker: PhantomData<T>,
+}
+
+impl<I, T> Iterator for Decode<I, T>
+where
+    I: Iterator<Item = u8>,
+    T: Value,
+{
+    type Item = Result<T, WideError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        decode_next(&mut self.iter, &mut self.pending, self.max_len)
+            .map(|result| result.map(|(value, _)| value))
+    }
+}
+
+impl<I, T> FusedIterator for Decode<I, T>
+where
+    I: FusedIterator<Item = u8>,
+    T: Value,
+{
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ume8::EncodeUnchecked;
+
+    pub(crate) fn values_u64() -> Vec<u64> {
+        let mut values = vec![0, u64::MAX];
+        for bits in 1..64 {
+            values.extend([(1 << bits) - 1, 1 << bits, (1 << bits) + 1]);
+        }
+        values
+    }
+
+    #[test]
+    fn test_max_len() {
+        assert_eq!(Codec::<u8>::MAX_LEN, 2);
+        assert_eq!(Codec::<u32>::MAX_LEN, 7);
+        assert_eq!(Codec::<u64>::MAX_LEN, 13);
+        assert_eq!(Codec::<u128>::MAX_LEN, 26);
+    }
+
+    #[test]
+    fn test_round_trip_u64() {
+        let codec = Codec::<u64>::new();
+
+        for value in values_u64() {
+            let mut bytes = Vec::new();
+            let len = codec.encode(value, &mut bytes).unwrap();
+
+            assert_eq!(len, encoded_len(value));
+            assert_eq!(codec.decode(&bytes), Ok((value, len)));
+        }
+
+        let values = values_u64();
+        let bytes = codec
+            .encoder(values.iter().copied())
+            .collect::<Result<Vec<u8>, _>>()
+            .unwrap();
+        let decoded = codec.decoder(bytes).collect::<Result<Vec<u64>, _>>();
+
+        assert_eq!(decoded, Ok(values));
+    }
+
+    #[test]
+    fn test_round_trip_u128() {
+        let codec = Codec::<u128>::new();
+
+        for bits in 0..128 {
+            for value in [1u128 << bits, (1u128 << bits) - 1, u128::MAX >> bits] {
+                let mut bytes = Vec::new();
+                let len = codec.encode(value, &mut bytes).unwrap();
+
+                assert_eq!(codec.decode(&bytes), Ok((value, len)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_grammar_as_ume8() {
+        let codec = Codec::<u32>::new();
+
+        for (decoded, encoded) in super::super::tests::data() {
+            let bytes = codec
+                .encoder(decoded.iter().copied())
+                .collect::<Result<Vec<u8>, _>>();
+
+            assert_eq!(bytes, Ok(encoded));
+        }
+
+        let mut bytes = Vec::new();
+        codec.encode(0x1FFFFF, &mut bytes).unwrap();
+        assert_eq!(
+            bytes,
+            EncodeUnchecked::new([0x1FFFFF].into_iter()).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn test_max_len_limit() {
+        let codec = Codec::<u64>::with_max_len(4);
+        let mut bytes = Vec::new();
+
+        assert_eq!(codec.encode(0x1FFFFF, &mut bytes), Ok(4));
+        assert_eq!(
+            codec.encode(0x200000, &mut bytes),
+            Err(WideError::SequenceTooLong)
+        );
+
+        let mut long = Vec::new();
+        Codec::<u64>::new().encode(0x200000, &mut long).unwrap();
+        assert_eq!(codec.decode(&long), Err(WideError::SequenceTooLong));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let codec = Codec::<u64>::new();
+
+        assert_eq!(codec.decode(&[]), Err(WideError::IncompleteSequence));
+        assert_eq!(
+            codec.decode(&[0b10100001]),
+            Err(WideError::UnexpectedByte(0b10100001))
+        );
+        assert_eq!(
+            codec.decode(&[0b11000001, b'a']),
+            Err(WideError::IncompleteSequence)
+        );
+        assert_eq!(
+            codec.decode(&[0b11000000, 0b10100001]),
+            Err(WideError::OverlongSequence)
+        );
+
+        // 66 bits
+        let mut bytes = vec![0b11111111];
+        bytes.extend([0b10011111; 11]);
+        bytes.push(0b10111111);
+        assert_eq!(codec.decode(&bytes), Err(WideError::Overflow));
+
+        // the interrupting byte starts the next value
+        let decoded = codec
+            .decoder([0b11000001, b'a', 0b10100001])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decoded,
+            vec![
+                Err(WideError::IncompleteSequence),
+                Ok(b'a' as u64),
+                Err(WideError::UnexpectedByte(0b10100001)),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_max_len_too_large() {
+        Codec::<u32>::with_max_len(8);
+    }
+}